@@ -0,0 +1,664 @@
+use crate::{
+    DagCnf, Lit, LitVec, LitVvec, Var,
+    dimacs::{create_compressed, open_compressed},
+};
+use giputils::hash::GHashMap;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+/// A latch with its next-state function, `init` is `None` when the initial
+/// value is nondeterministic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AigLatch {
+    pub var: Var,
+    pub next: Lit,
+    pub init: Option<bool>,
+}
+
+/// An AIGER 1.9 circuit. The AND gates live in `dc` as `cnf_and` relations and
+/// AIGER literal `l` is the `Lit` with the same encoding, so variable `k` is
+/// `Var(k)` and literal 0 is `Lit::constant(false)`. [`read_aiger`] renumbers
+/// ASCII circuits whose AND gates are not above their inputs.
+#[derive(Debug, Clone, Default)]
+pub struct Aiger {
+    pub dc: DagCnf,
+    pub inputs: Vec<Var>,
+    pub latchs: Vec<AigLatch>,
+    pub outputs: LitVec,
+    pub bads: LitVec,
+    pub constraints: LitVec,
+    pub justice: Vec<LitVec>,
+    pub fairness: LitVec,
+}
+
+impl Aiger {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn new_input(&mut self) -> Var {
+        let v = self.dc.new_var();
+        self.inputs.push(v);
+        v
+    }
+
+    /// Adds a latch whose next-state function is set later through
+    /// `latchs`.
+    #[inline]
+    pub fn new_latch(&mut self, init: Option<bool>) -> Var {
+        let var = self.dc.new_var();
+        self.latchs.push(AigLatch {
+            var,
+            next: Lit::constant(false),
+            init,
+        });
+        var
+    }
+}
+
+#[derive(Debug)]
+pub enum AigerErrorKind {
+    Io(io::Error),
+    InvalidHeader,
+    InvalidLiteral(String),
+    UnexpectedEof,
+    /// A binary AND gate whose inputs are not below its output, or an AND
+    /// gate that depends on itself.
+    NotTopological(u32),
+    Redefined(u32),
+    Undefined(u32),
+}
+
+#[derive(Debug)]
+pub struct AigerError {
+    pub line: usize,
+    pub kind: AigerErrorKind,
+}
+
+impl Display for AigerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "aiger {}: ", self.line)?;
+        match &self.kind {
+            AigerErrorKind::Io(e) => write!(f, "io error: {e}"),
+            AigerErrorKind::InvalidHeader => {
+                write!(f, "invalid header, expect `aag|aig M I L O A`")
+            }
+            AigerErrorKind::InvalidLiteral(t) => write!(f, "invalid literal `{t}`"),
+            AigerErrorKind::UnexpectedEof => write!(f, "unexpected end of file"),
+            AigerErrorKind::NotTopological(l) => {
+                write!(f, "and gate {l} is not above its inputs")
+            }
+            AigerErrorKind::Redefined(l) => write!(f, "literal {l} defined twice"),
+            AigerErrorKind::Undefined(l) => write!(f, "literal {l} is not defined"),
+        }
+    }
+}
+
+impl Error for AigerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            AigerErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+struct AigerParser<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    line: usize,
+    max_var: u32,
+    defined: Vec<bool>,
+    /// Used variables with the line using them, checked once all are defined.
+    used: Vec<(Var, usize)>,
+}
+
+impl<'a> AigerParser<'a> {
+    fn error(&self, kind: AigerErrorKind) -> AigerError {
+        AigerError {
+            line: self.line,
+            kind,
+        }
+    }
+
+    fn next_line(&mut self) -> Result<Vec<&'a str>, AigerError> {
+        if self.pos >= self.buf.len() {
+            return Err(self.error(AigerErrorKind::UnexpectedEof));
+        }
+        let end = self.buf[self.pos..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(self.buf.len(), |p| self.pos + p);
+        let buf: &'a [u8] = self.buf;
+        let line = std::str::from_utf8(&buf[self.pos..end]).unwrap_or("\u{fffd}");
+        self.pos = end + 1;
+        self.line += 1;
+        Ok(line.split_ascii_whitespace().collect())
+    }
+
+    fn lit(&self, t: &str) -> Result<Lit, AigerError> {
+        t.parse::<u32>()
+            .ok()
+            .filter(|l| l >> 1 <= self.max_var)
+            .map(|l| Lit::new(Var(l >> 1), l & 1 == 0))
+            .ok_or_else(|| self.error(AigerErrorKind::InvalidLiteral(t.to_string())))
+    }
+
+    fn use_lit(&mut self, t: &str) -> Result<Lit, AigerError> {
+        let l = self.lit(t)?;
+        self.used.push((l.var(), self.line));
+        Ok(l)
+    }
+
+    fn lits(&mut self, n: usize) -> Result<LitVec, AigerError> {
+        (0..n)
+            .map(|_| match self.next_line()?.as_slice() {
+                [t] => self.use_lit(t),
+                t => Err(self.error(AigerErrorKind::InvalidLiteral(t.join(" ")))),
+            })
+            .collect()
+    }
+
+    fn define(&mut self, t: &str) -> Result<Var, AigerError> {
+        let l = self.lit(t)?;
+        if !l.polarity() || l.var().is_constant() {
+            return Err(self.error(AigerErrorKind::InvalidLiteral(t.to_string())));
+        }
+        let d = &mut self.defined[l.var().0 as usize];
+        if *d {
+            return Err(self.error(AigerErrorKind::Redefined(u32::from(l))));
+        }
+        *d = true;
+        Ok(l.var())
+    }
+
+    fn varint(&mut self) -> Result<u32, AigerError> {
+        let mut x = 0u32;
+        for i in 0..5 {
+            let Some(&b) = self.buf.get(self.pos) else {
+                return Err(self.error(AigerErrorKind::UnexpectedEof));
+            };
+            self.pos += 1;
+            x |= ((b & 0x7f) as u32) << (7 * i);
+            if b & 0x80 == 0 {
+                return Ok(x);
+            }
+        }
+        Err(self.error(AigerErrorKind::InvalidLiteral("varint".to_string())))
+    }
+
+    fn add_and(&self, dc: &mut DagCnf, lhs: Var, r0: Lit, r1: Lit) -> Result<(), AigerError> {
+        if r0.var() >= lhs || r1.var() >= lhs {
+            return Err(self.error(AigerErrorKind::NotTopological(u32::from(lhs.lit()))));
+        }
+        dc.add_rel(lhs, &LitVvec::cnf_and(lhs.lit(), &[r0, r1]));
+        Ok(())
+    }
+
+    fn check_used(&self) -> Result<(), AigerError> {
+        match self
+            .used
+            .iter()
+            .find(|(v, _)| !v.is_constant() && !self.defined[v.0 as usize])
+        {
+            Some((v, line)) => Err(AigerError {
+                line: *line,
+                kind: AigerErrorKind::Undefined(u32::from(v.lit())),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Orders the ASCII AND gates `(lhs, r0, r1, line)` so that each follows the
+/// gates it depends on.
+fn topo_order(ands: &[(Var, Lit, Lit, usize)]) -> Result<Vec<usize>, AigerError> {
+    let gate: GHashMap<Var, usize> = ands.iter().enumerate().map(|(k, a)| (a.0, k)).collect();
+    // 0: unvisited, 1: on the stack, 2: ordered.
+    let mut state = vec![0u8; ands.len()];
+    let mut order = Vec::with_capacity(ands.len());
+    for s in 0..ands.len() {
+        if state[s] != 0 {
+            continue;
+        }
+        state[s] = 1;
+        let mut stack = vec![s];
+        while let Some(&g) = stack.last() {
+            let (_, r0, r1, _) = ands[g];
+            let mut child = None;
+            for r in [r0, r1] {
+                let Some(&c) = gate.get(&r.var()) else {
+                    continue;
+                };
+                match state[c] {
+                    0 => {
+                        child = Some(c);
+                        break;
+                    }
+                    1 => {
+                        let (lhs, _, _, line) = ands[c];
+                        return Err(AigerError {
+                            line,
+                            kind: AigerErrorKind::NotTopological(u32::from(lhs.lit())),
+                        });
+                    }
+                    _ => (),
+                }
+            }
+            match child {
+                Some(c) => {
+                    state[c] = 1;
+                    stack.push(c);
+                }
+                None => {
+                    state[g] = 2;
+                    order.push(g);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Renumbers `aig` into the binary order, inputs first, then latches, then
+/// the AND gates in `order`, and adds the gates to its `dc`.
+fn renumber(aig: &mut Aiger, ands: &[(Var, Lit, Lit, usize)], order: &[usize], max_var: usize) {
+    let mut map = vec![Var::CONST; max_var + 1];
+    let vars = aig
+        .inputs
+        .iter()
+        .chain(aig.latchs.iter().map(|l| &l.var))
+        .copied()
+        .chain(order.iter().map(|g| ands[*g].0));
+    for (k, v) in vars.enumerate() {
+        map[v.0 as usize] = Var::new(k + 1);
+    }
+    let ml = |l: Lit| map[l.var().0 as usize].lit().not_if(!l.polarity());
+    let mvec = |ls: &mut LitVec| ls.iter_mut().for_each(|l| *l = ml(*l));
+    aig.dc = DagCnf::new();
+    aig.dc
+        .new_var_to(Var::new(aig.inputs.len() + aig.latchs.len() + ands.len()));
+    for v in aig.inputs.iter_mut() {
+        *v = map[v.0 as usize];
+    }
+    for l in aig.latchs.iter_mut() {
+        l.var = map[l.var.0 as usize];
+        l.next = ml(l.next);
+    }
+    mvec(&mut aig.outputs);
+    mvec(&mut aig.bads);
+    mvec(&mut aig.constraints);
+    aig.justice.iter_mut().for_each(mvec);
+    mvec(&mut aig.fairness);
+    for &g in order {
+        let (lhs, r0, r1, _) = ands[g];
+        let lhs = ml(lhs.lit());
+        aig.dc
+            .add_rel(lhs.var(), &LitVvec::cnf_and(lhs, &[ml(r0), ml(r1)]));
+    }
+}
+
+/// Reads an AIGER 1.9 circuit in either the ASCII (`aag`) or the binary
+/// (`aig`) format. The symbol table and comments are skipped. An ASCII circuit
+/// whose AND gates are not all above their inputs is renumbered into the
+/// order of the binary format, otherwise literals keep their encoding.
+pub fn read_aiger<R: BufRead>(mut reader: R) -> Result<Aiger, AigerError> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).map_err(|e| AigerError {
+        line: 0,
+        kind: AigerErrorKind::Io(e),
+    })?;
+    let mut p = AigerParser {
+        buf: &buf,
+        pos: 0,
+        line: 0,
+        max_var: 0,
+        defined: Vec::new(),
+        used: Vec::new(),
+    };
+    let header = p.next_line()?;
+    let binary = match header.first() {
+        Some(&"aag") => false,
+        Some(&"aig") => true,
+        _ => return Err(p.error(AigerErrorKind::InvalidHeader)),
+    };
+    if header.len() < 6 || header.len() > 10 {
+        return Err(p.error(AigerErrorKind::InvalidHeader));
+    }
+    let mut h = [0usize; 9];
+    for (x, t) in h.iter_mut().zip(header[1..].iter()) {
+        *x = t
+            .parse()
+            .map_err(|_| p.error(AigerErrorKind::InvalidHeader))?;
+    }
+    let [m, i, l, o, a, b, c, j, f] = h;
+    if m < i + l + a || m >= (u32::MAX >> 1) as usize {
+        return Err(p.error(AigerErrorKind::InvalidHeader));
+    }
+    p.max_var = m as u32;
+    p.defined = vec![false; m + 1];
+    let mut aig = Aiger::new();
+    aig.dc.new_var_to(Var::new(m));
+    for k in 0..i {
+        let var = if binary {
+            let var = Var::new(k + 1);
+            p.defined[k + 1] = true;
+            var
+        } else {
+            match p.next_line()?.as_slice() {
+                [t] => p.define(t)?,
+                t => return Err(p.error(AigerErrorKind::InvalidLiteral(t.join(" ")))),
+            }
+        };
+        aig.inputs.push(var);
+    }
+    for k in 0..l {
+        let line = p.next_line()?;
+        let (var, rest) = if binary {
+            let var = Var::new(i + k + 1);
+            p.defined[i + k + 1] = true;
+            (var, &line[..])
+        } else {
+            let Some((t, rest)) = line.split_first() else {
+                return Err(p.error(AigerErrorKind::InvalidLiteral(String::new())));
+            };
+            (p.define(t)?, rest)
+        };
+        let (next, init) = match rest {
+            [n] => (p.use_lit(n)?, Some(false)),
+            [n, r] => {
+                let init = match p.lit(r)? {
+                    x if x.is_constant(false) => Some(false),
+                    x if x.is_constant(true) => Some(true),
+                    x if x == var.lit() => None,
+                    _ => return Err(p.error(AigerErrorKind::InvalidLiteral(r.to_string()))),
+                };
+                (p.use_lit(n)?, init)
+            }
+            t => return Err(p.error(AigerErrorKind::InvalidLiteral(t.join(" ")))),
+        };
+        aig.latchs.push(AigLatch { var, next, init });
+    }
+    aig.outputs = p.lits(o)?;
+    aig.bads = p.lits(b)?;
+    aig.constraints = p.lits(c)?;
+    let mut sizes = Vec::with_capacity(j);
+    for _ in 0..j {
+        match p.next_line()?.as_slice() {
+            [t] => sizes.push(
+                t.parse::<usize>()
+                    .map_err(|_| p.error(AigerErrorKind::InvalidLiteral(t.to_string())))?,
+            ),
+            t => return Err(p.error(AigerErrorKind::InvalidLiteral(t.join(" ")))),
+        }
+    }
+    for s in sizes {
+        let lits = p.lits(s)?;
+        aig.justice.push(lits);
+    }
+    aig.fairness = p.lits(f)?;
+    let mut ands = Vec::with_capacity(if binary { 0 } else { a });
+    for k in 0..a {
+        if binary {
+            let lhs = 2 * (i + l + k + 1) as u32;
+            let d0 = p.varint()?;
+            let d1 = p.varint()?;
+            let r1 = d0.checked_add(d1).and_then(|d| lhs.checked_sub(d));
+            let (Some(r0), Some(r1)) = (lhs.checked_sub(d0), r1) else {
+                return Err(p.error(AigerErrorKind::NotTopological(lhs)));
+            };
+            let r0 = Lit::new(Var(r0 >> 1), r0 & 1 == 0);
+            let r1 = Lit::new(Var(r1 >> 1), r1 & 1 == 0);
+            p.add_and(&mut aig.dc, Var(lhs >> 1), r0, r1)?;
+            p.defined[(lhs >> 1) as usize] = true;
+        } else {
+            match p.next_line()?.as_slice() {
+                [lhs, r0, r1] => {
+                    let lhs = p.define(lhs)?;
+                    let (r0, r1) = (p.use_lit(r0)?, p.use_lit(r1)?);
+                    ands.push((lhs, r0, r1, p.line));
+                }
+                t => return Err(p.error(AigerErrorKind::InvalidLiteral(t.join(" ")))),
+            }
+        }
+    }
+    p.check_used()?;
+    if ands
+        .iter()
+        .all(|(lhs, r0, r1, _)| r0.var() < *lhs && r1.var() < *lhs)
+    {
+        for (lhs, r0, r1, _) in ands {
+            aig.dc.add_rel(lhs, &LitVvec::cnf_and(lhs.lit(), &[r0, r1]));
+        }
+    } else {
+        let order = topo_order(&ands)?;
+        renumber(&mut aig, &ands, &order, m);
+    }
+    Ok(aig)
+}
+
+pub fn read_aiger_file<P: AsRef<Path>>(file: P) -> Result<Aiger, AigerError> {
+    let file = open_compressed(file).map_err(|e| AigerError {
+        line: 0,
+        kind: AigerErrorKind::Io(e),
+    })?;
+    read_aiger(file)
+}
+
+/// Returns `(o, ins)` if the relation of `n` is `cnf_and(o, ins)` with `o` a
+/// literal of `n`.
+fn and_shape(n: Var, rel: &[LitVec]) -> Option<(Lit, Vec<Lit>)> {
+    for o in [n.lit(), !n.lit()] {
+        let ins: Vec<Lit> = rel
+            .iter()
+            .filter(|c| c.len() == 2 && c.contains(&!o))
+            .map(|c| if c[0] == !o { c[1] } else { c[0] })
+            .collect();
+        if ins.is_empty() || rel.len() != ins.len() + 1 {
+            continue;
+        }
+        let big = rel.iter().find(|c| c.contains(&o))?;
+        if big.len() == ins.len() + 1 && ins.iter().all(|l| big.contains(&!*l)) {
+            return Some((o, ins));
+        }
+    }
+    None
+}
+
+/// Writes `aig` in the ASCII (`aag`) or binary (`aig`) format. Only the cone
+/// of the outputs, properties and latches is emitted. Its relations must be
+/// `cnf_and`/`cnf_or`-shaped, wider gates are split into binary ANDs and
+/// variables are renumbered in the AIGER order.
+pub fn write_aiger<W: Write>(aig: &Aiger, mut out: W, binary: bool) -> io::Result<()> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+    let roots = aig
+        .latchs
+        .iter()
+        .map(|l| l.next)
+        .chain(aig.outputs.iter().copied())
+        .chain(aig.bads.iter().copied())
+        .chain(aig.constraints.iter().copied())
+        .chain(aig.justice.iter().flatten().copied())
+        .chain(aig.fairness.iter().copied());
+    let cone = aig.dc.fanins(roots.map(|l| l.var()));
+    let mut map: GHashMap<Var, Lit> = GHashMap::new();
+    map.insert(Var::CONST, Lit::constant(false));
+    let mut num_var = 0u32;
+    for v in aig.inputs.iter().chain(aig.latchs.iter().map(|l| &l.var)) {
+        if !aig.dc.is_leaf(*v) {
+            return Err(invalid(format!("input or latch {v} has a relation")));
+        }
+        num_var += 1;
+        map.insert(*v, Var(num_var).lit());
+    }
+    let mut ands: Vec<(u32, Lit, Lit)> = Vec::new();
+    for v in aig.dc.var_iter() {
+        if !cone.contains(&v) || map.contains_key(&v) {
+            continue;
+        }
+        if aig.dc.is_leaf(v) {
+            return Err(invalid(format!("variable {v} is neither input nor latch")));
+        }
+        let Some((o, ins)) = and_shape(v, &aig.dc[v]) else {
+            return Err(invalid(format!("relation of {v} is not an and gate")));
+        };
+        let ml = |l: &Lit| map[&l.var()].not_if(!l.polarity());
+        let mut acc = ml(&ins[0]);
+        for l in ins[1..].iter() {
+            let (x, y) = (acc, ml(l));
+            num_var += 1;
+            ands.push((num_var << 1, x.max(y), x.min(y)));
+            acc = Var(num_var).lit();
+        }
+        map.insert(v, acc.not_if(!o.polarity()));
+    }
+    let ml = |l: Lit| u32::from(map[&l.var()].not_if(!l.polarity()));
+    let (i, l) = (aig.inputs.len(), aig.latchs.len());
+    write!(
+        out,
+        "{} {num_var} {i} {l} {} {}",
+        if binary { "aig" } else { "aag" },
+        aig.outputs.len(),
+        ands.len()
+    )?;
+    let extra = [
+        aig.bads.len(),
+        aig.constraints.len(),
+        aig.justice.len(),
+        aig.fairness.len(),
+    ];
+    let n = extra.iter().rposition(|x| *x > 0).map_or(0, |p| p + 1);
+    for x in extra[..n].iter() {
+        write!(out, " {x}")?;
+    }
+    writeln!(out)?;
+    if !binary {
+        for v in aig.inputs.iter() {
+            writeln!(out, "{}", ml(v.lit()))?;
+        }
+    }
+    for latch in aig.latchs.iter() {
+        if !binary {
+            write!(out, "{} ", ml(latch.var.lit()))?;
+        }
+        write!(out, "{}", ml(latch.next))?;
+        match latch.init {
+            Some(false) => writeln!(out)?,
+            Some(true) => writeln!(out, " 1")?,
+            None => writeln!(out, " {}", ml(latch.var.lit()))?,
+        }
+    }
+    for lit in aig
+        .outputs
+        .iter()
+        .chain(aig.bads.iter())
+        .chain(aig.constraints.iter())
+    {
+        writeln!(out, "{}", ml(*lit))?;
+    }
+    for j in aig.justice.iter() {
+        writeln!(out, "{}", j.len())?;
+    }
+    for lit in aig.justice.iter().flatten().chain(aig.fairness.iter()) {
+        writeln!(out, "{}", ml(*lit))?;
+    }
+    for (lhs, r0, r1) in ands {
+        let (r0, r1) = (u32::from(r0), u32::from(r1));
+        if binary {
+            for mut d in [lhs - r0, r0 - r1] {
+                while d >= 0x80 {
+                    out.write_all(&[(d & 0x7f) as u8 | 0x80])?;
+                    d >>= 7;
+                }
+                out.write_all(&[d as u8])?;
+            }
+        } else {
+            writeln!(out, "{lhs} {r0} {r1}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the ASCII format when the file name ends with `.aag`, ignoring a
+/// compression suffix, and the binary format otherwise.
+pub fn write_aiger_file<P: AsRef<Path>>(aig: &Aiger, file: P) -> io::Result<()> {
+    let name = file.as_ref().to_string_lossy();
+    let name = name
+        .strip_suffix(".gz")
+        .or_else(|| name.strip_suffix(".xz"))
+        .unwrap_or(&name);
+    let binary = !name.ends_with(".aag");
+    let mut file = create_compressed(file)?;
+    write_aiger(aig, &mut file, binary)?;
+    file.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dagcnf::test_util::truth_table;
+
+    const AAG: &str = "aag 7 2 2 1 3 1 1 1 1\n2\n4\n6 13\n8 14 8\n10\n12\n8\n1\n6\n2\n10 6 3\n12 10 5\n14 7 2\nc\ncomment\n";
+
+    #[test]
+    fn test_aiger() {
+        let aig = read_aiger(AAG.as_bytes()).unwrap();
+        assert_eq!(aig.inputs, vec![Var(1), Var(2)]);
+        assert_eq!(aig.latchs[1].init, None);
+        assert_eq!(aig.justice.len(), 1);
+        let mut ascii = Vec::new();
+        write_aiger(&aig, &mut ascii, false).unwrap();
+        assert_eq!(
+            String::from_utf8(ascii.clone()).unwrap(),
+            &AAG[..AAG.len() - 10]
+        );
+        let mut bin = Vec::new();
+        write_aiger(&aig, &mut bin, true).unwrap();
+        let back = read_aiger(bin.as_slice()).unwrap();
+        let mut ascii2 = Vec::new();
+        write_aiger(&back, &mut ascii2, false).unwrap();
+        assert_eq!(ascii, ascii2);
+        assert!(read_aiger("aag 1 0 0 0 1\n2 2 0\n".as_bytes()).is_err());
+        assert!(read_aiger("aig 1 1 0 0 0 1\n".as_bytes()).is_err());
+        let overflow = [&b"aig 1 0 0 0 1\n"[..], &[1, 0xff, 0xff, 0xff, 0xff, 0x0f]].concat();
+        let err = read_aiger(overflow.as_slice()).unwrap_err();
+        assert!(matches!(err.kind, AigerErrorKind::NotTopological(2)));
+        let err = read_aiger("aag 3 1 0 1 1\n2\n4\n4 2 6\n".as_bytes()).unwrap_err();
+        assert_eq!(err.line, 4);
+        assert!(matches!(err.kind, AigerErrorKind::Undefined(6)));
+        let err = read_aiger("aag 2 0 0 1 0\n4\n".as_bytes()).unwrap_err();
+        assert!(matches!(err.kind, AigerErrorKind::Undefined(4)));
+        let err = read_aiger("aag 2 0 0 0 2\n2 4 0\n4 3 1\n".as_bytes()).unwrap_err();
+        assert!(matches!(err.kind, AigerErrorKind::NotTopological(_)));
+
+        let unordered = "aag 4 2 0 1 2\n8\n4\n3\n2 6 5\n6 8 1\n";
+        let aig = read_aiger(unordered.as_bytes()).unwrap();
+        assert_eq!(aig.inputs, vec![Var(1), Var(2)]);
+        let x: Vec<Lit> = aig.inputs.iter().map(|v| v.lit()).collect();
+        let table: Vec<Vec<bool>> = (0..4).map(|m| vec![!(m & 1 == 1 && m & 2 == 0)]).collect();
+        assert_eq!(truth_table(&aig.dc, &x, &aig.outputs), table);
+
+        let mut aig = Aiger::new();
+        let x: Vec<Lit> = (0..3).map(|_| aig.new_input().lit()).collect();
+        let a = aig.dc.new_and([x[0], !x[1], x[2]]);
+        let o = aig.dc.new_or([a, x[1]]);
+        aig.outputs.push(!o);
+        let mut bin = Vec::new();
+        write_aiger(&aig, &mut bin, true).unwrap();
+        let back = read_aiger(bin.as_slice()).unwrap();
+        let y: Vec<Lit> = back.inputs.iter().map(|v| v.lit()).collect();
+        assert_eq!(
+            truth_table(&aig.dc, &x, &aig.outputs),
+            truth_table(&back.dc, &y, &back.outputs)
+        );
+        aig.outputs.push(aig.dc.new_xor(x[0], x[1]));
+        assert!(write_aiger(&aig, io::sink(), false).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Lbool, Lit, Var, VarMap};
+use crate::{Lbool, Lit, LitVec, Var, VarMap};
 
 #[derive(Clone)]
 pub struct VarAssign {
@@ -47,6 +47,32 @@ impl VarAssign {
     pub fn set_none(&mut self, var: Var) {
         self.v[var] = Lbool::NONE
     }
+
+    /// Value of `lit`, `Lbool::NONE` if its variable is out of range.
+    #[inline]
+    pub fn v_checked(&self, lit: Lit) -> Lbool {
+        match self.v.get(*lit.var() as usize) {
+            Some(v) => Lbool(v.0 ^ (!lit.polarity() as u8)),
+            None => Lbool::NONE,
+        }
+    }
+
+    #[inline]
+    pub fn satisfy(&self, cls: &[Lit]) -> bool {
+        cls.iter().any(|l| self.v_checked(*l).is_true())
+    }
+
+    /// Checks that the assignment satisfies every clause and returns the
+    /// first one that is not. Unassigned literals do not satisfy a clause.
+    pub fn check<'a>(
+        &self,
+        clauses: impl IntoIterator<Item = &'a LitVec>,
+    ) -> Result<(), &'a LitVec> {
+        match clauses.into_iter().find(|c| !self.satisfy(c)) {
+            Some(c) => Err(c),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Default for VarAssign {
@@ -1,4 +1,4 @@
-use crate::{DagCnf, Lit, LitVec, Var, VarVMap};
+use crate::{DagCnf, Lit, LitVec, Var, VarAssign, VarVMap};
 use giputils::hash::GHashSet;
 use std::{
     iter::once,
@@ -52,6 +52,11 @@ impl Cnf {
         &self.cls
     }
 
+    #[inline]
+    pub fn check_model(&self, model: &VarAssign) -> Result<(), &LitVec> {
+        model.check(self.cls.iter())
+    }
+
     pub fn rearrange(&mut self, additional: impl Iterator<Item = Var>) -> VarVMap {
         let mut domain = GHashSet::from_iter(additional.chain(once(Var::CONST)));
         for cls in self.cls.iter() {
@@ -0,0 +1,119 @@
+use super::pow2;
+use crate::{
+    Cnf, Lit, LitVvec, Var,
+    satif::{AllSat, CdclSolver, Satif},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Hashing based approximate projected model counter in the style of
+/// ApproxMC. Random XOR constraints over the projection, encoded as
+/// `LitVvec::cnf_xor` chains, split the solution space into cells which are
+/// counted by bounded enumeration. The estimate is within a factor of
+/// `1 + epsilon` of the exact count with probability at least `1 - delta`.
+pub struct ApproxCounter {
+    solver: CdclSolver,
+    proj: Vec<Var>,
+    epsilon: f64,
+    delta: f64,
+    rng: StdRng,
+}
+
+impl ApproxCounter {
+    /// Counts over all variables of `cnf` except `Var::CONST`.
+    pub fn new(cnf: &Cnf) -> Self {
+        let mut solver = CdclSolver::new();
+        solver.new_var_to(cnf.max_var());
+        for c in cnf.iter() {
+            solver.add_clause(c);
+        }
+        Self {
+            solver,
+            proj: (1..=*cnf.max_var()).map(Var).collect(),
+            epsilon: 0.8,
+            delta: 0.2,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    pub fn with_projection(mut self, proj: &[Var]) -> Self {
+        if let Some(m) = proj.iter().max() {
+            self.solver.new_var_to(*m);
+        }
+        self.proj = proj.to_vec();
+        self
+    }
+
+    pub fn with_tolerance(mut self, epsilon: f64, delta: f64) -> Self {
+        assert!(epsilon > 0.0 && delta > 0.0 && delta < 1.0);
+        self.epsilon = epsilon;
+        self.delta = delta;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    fn threshold(&self) -> usize {
+        let e = self.epsilon;
+        (1.0 + 9.84 * (1.0 + e / (1.0 + e)) * (1.0 + 1.0 / e).powi(2)).ceil() as usize
+    }
+
+    fn cell_count(&mut self, assumps: &[Lit], thresh: usize) -> usize {
+        let res = AllSat::new(&mut self.solver, &self.proj, assumps)
+            .take(thresh + 1)
+            .count();
+        self.solver.simplify();
+        res
+    }
+
+    /// Adds a random XOR over the projection and returns the literal which
+    /// asserts it.
+    fn random_xor(&mut self) -> Lit {
+        let vars: Vec<Var> = self
+            .proj
+            .iter()
+            .copied()
+            .filter(|_| self.rng.random())
+            .collect();
+        let parity: bool = self.rng.random();
+        let Some((&first, rest)) = vars.split_first() else {
+            return Lit::constant(!parity);
+        };
+        let mut y = first.lit();
+        for &x in rest {
+            let n = self.solver.new_var().lit();
+            for c in LitVvec::cnf_xor(n, y, x.lit()).iter() {
+                self.solver.add_clause(c);
+            }
+            y = n;
+        }
+        y.not_if(!parity)
+    }
+
+    /// The estimated count, `None` if it does not fit in a `u128`.
+    pub fn count(&mut self) -> Option<u128> {
+        let thresh = self.threshold();
+        let n = self.cell_count(&[], thresh);
+        if n <= thresh {
+            return Some(n as u128);
+        }
+        let iteration = (17.0 * (3.0 / self.delta).log2()).ceil() as usize;
+        let mut estimates = Vec::with_capacity(iteration);
+        for _ in 0..iteration {
+            let mut assumps = Vec::new();
+            for m in 1..=self.proj.len() {
+                assumps.push(self.random_xor());
+                let n = self.cell_count(&assumps, thresh);
+                if n <= thresh || m == self.proj.len() {
+                    estimates.push(pow2(m).and_then(|p| p.checked_mul(n as u128)));
+                    break;
+                }
+            }
+        }
+        // An overflowing estimate is larger than any other.
+        estimates.sort_by_key(|e| (e.is_none(), *e));
+        estimates[estimates.len() / 2]
+    }
+}
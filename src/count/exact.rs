@@ -0,0 +1,205 @@
+use super::pow2;
+use crate::{
+    Cnf, Lit, LitVec, Var, VarMap,
+    satif::{CdclSolver, Satif},
+};
+use giputils::hash::{GHashMap, GHashSet};
+
+#[derive(Debug, Default, Clone)]
+pub struct ExactCounterStatistic {
+    pub num_decision: usize,
+    pub num_cache_hit: usize,
+    pub num_component: usize,
+}
+
+/// Exact projected model counter, a DPLL search that splits the residual
+/// formula into independent components and caches their counts. Only
+/// projected variables are branched on; components without projected
+/// variables are checked for satisfiability with `CdclSolver`.
+pub struct ExactCounter {
+    clauses: Vec<LitVec>,
+    proj: Vec<Var>,
+    is_proj: VarMap<bool>,
+    cache: GHashMap<Vec<LitVec>, Option<u128>>,
+    statistic: ExactCounterStatistic,
+}
+
+impl ExactCounter {
+    /// Counts over all variables of `cnf` except `Var::CONST`. Use
+    /// `DagCnf::lower` to count a `DagCnf`.
+    pub fn new(cnf: &Cnf) -> Self {
+        let proj: Vec<Var> = (1..=*cnf.max_var()).map(Var).collect();
+        let mut clauses: Vec<LitVec> = Vec::with_capacity(cnf.len());
+        for c in cnf.iter() {
+            let mut c = c.clone();
+            c.sort();
+            c.dedup();
+            if c.windows(2).all(|w| w[0] != !w[1]) {
+                clauses.push(c);
+            }
+        }
+        let mut res = Self {
+            clauses,
+            proj: Vec::new(),
+            is_proj: VarMap::new_with(cnf.max_var()),
+            cache: GHashMap::new(),
+            statistic: ExactCounterStatistic::default(),
+        };
+        res.set_projection(&proj);
+        res
+    }
+
+    pub fn with_projection(mut self, proj: &[Var]) -> Self {
+        self.set_projection(proj);
+        self
+    }
+
+    fn set_projection(&mut self, proj: &[Var]) {
+        self.is_proj.iter_mut().for_each(|p| *p = false);
+        self.proj = proj.to_vec();
+        self.proj.sort();
+        self.proj.dedup();
+        for &v in self.proj.iter() {
+            self.is_proj.reserve(v);
+            self.is_proj[v] = true;
+        }
+        self.cache.clear();
+    }
+
+    #[inline]
+    pub fn statistic(&self) -> &ExactCounterStatistic {
+        &self.statistic
+    }
+
+    /// Number of assignments to the projection that extend to a model, `None`
+    /// if it does not fit in a `u128`.
+    pub fn count(&mut self) -> Option<u128> {
+        let clauses = self.clauses.clone();
+        let proj = self.proj.clone();
+        self.count_formula(clauses, &proj)
+    }
+
+    fn count_formula(&mut self, mut clauses: Vec<LitVec>, pvars: &[Var]) -> Option<u128> {
+        let mut assigned = GHashSet::new();
+        if !propagate(&mut clauses, &mut assigned) {
+            return Some(0);
+        }
+        let occur: GHashSet<Var> = clauses.iter().flatten().map(|l| l.var()).collect();
+        let free = pvars
+            .iter()
+            .filter(|v| !occur.contains(v) && !assigned.contains(*v))
+            .count();
+        // An unsatisfiable component makes the count 0 even if others overflow.
+        let mut res = pow2(free);
+        for comp in components(clauses) {
+            match self.count_component(comp) {
+                Some(0) => return Some(0),
+                c => res = res.zip(c).and_then(|(r, c)| r.checked_mul(c)),
+            }
+        }
+        res
+    }
+
+    fn count_component(&mut self, mut comp: Vec<LitVec>) -> Option<u128> {
+        self.statistic.num_component += 1;
+        comp.sort();
+        if let Some(c) = self.cache.get(&comp) {
+            self.statistic.num_cache_hit += 1;
+            return *c;
+        }
+        let mut occur: GHashMap<Var, usize> = GHashMap::new();
+        for l in comp.iter().flatten() {
+            if self.is_proj.get(*l.var() as usize).is_some_and(|p| *p) {
+                *occur.entry(l.var()).or_default() += 1;
+            }
+        }
+        let res = match occur.iter().max_by_key(|(v, n)| (**n, **v)) {
+            None => {
+                let mut solver = CdclSolver::new();
+                for c in comp.iter() {
+                    solver.add_clause(c);
+                }
+                Some(solver.solve(&[]) as u128)
+            }
+            Some((&v, _)) => {
+                self.statistic.num_decision += 1;
+                let pvars: Vec<Var> = occur.keys().copied().filter(|p| *p != v).collect();
+                let mut res = Some(0u128);
+                for lit in [v.lit(), !v.lit()] {
+                    if let Some(c) = assign(&comp, lit) {
+                        let c = self.count_formula(c, &pvars);
+                        res = res.zip(c).and_then(|(r, c)| r.checked_add(c));
+                    }
+                }
+                res
+            }
+        };
+        self.cache.insert(comp, res);
+        res
+    }
+}
+
+/// Simplifies `clauses` under `lit`, `None` on an empty clause.
+fn assign(clauses: &[LitVec], lit: Lit) -> Option<Vec<LitVec>> {
+    let mut res = Vec::with_capacity(clauses.len());
+    for c in clauses.iter() {
+        if c.contains(&lit) {
+            continue;
+        }
+        let c: LitVec = c.iter().filter(|l| **l != !lit).copied().collect();
+        if c.is_empty() {
+            return None;
+        }
+        res.push(c);
+    }
+    Some(res)
+}
+
+fn propagate(clauses: &mut Vec<LitVec>, assigned: &mut GHashSet<Var>) -> bool {
+    while let Some(u) = clauses.iter().find(|c| c.len() == 1) {
+        let u = u[0];
+        assigned.insert(u.var());
+        match assign(clauses, u) {
+            Some(c) => *clauses = c,
+            None => return false,
+        }
+    }
+    true
+}
+
+fn components(clauses: Vec<LitVec>) -> Vec<Vec<LitVec>> {
+    let mut index: GHashMap<Var, usize> = GHashMap::new();
+    let mut parent: Vec<usize> = Vec::new();
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    for c in clauses.iter() {
+        let mut root = None;
+        for l in c.iter() {
+            let i = *index.entry(l.var()).or_insert_with(|| {
+                parent.push(parent.len());
+                parent.len() - 1
+            });
+            let r = find(&mut parent, i);
+            match root {
+                None => root = Some(r),
+                Some(root) => parent[r] = root,
+            }
+        }
+    }
+    let mut comp: GHashMap<usize, usize> = GHashMap::new();
+    let mut res: Vec<Vec<LitVec>> = Vec::new();
+    for c in clauses {
+        let r = find(&mut parent, index[&c[0].var()]);
+        let i = *comp.entry(r).or_insert_with(|| {
+            res.push(Vec::new());
+            res.len() - 1
+        });
+        res[i].push(c);
+    }
+    res
+}
@@ -0,0 +1,96 @@
+mod approx;
+mod exact;
+
+pub use approx::*;
+pub use exact::*;
+
+/// `2^n`, `None` if it does not fit in a `u128`.
+#[inline]
+fn pow2(n: usize) -> Option<u128> {
+    1u128.checked_shl(u32::try_from(n).ok()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Cnf, Lit, LitVec, Var};
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn brute_force(num_var: u32, cnf: &Cnf, proj: &[Var]) -> u128 {
+        let mut models = std::collections::HashSet::new();
+        for m in 0..1u32 << num_var {
+            let v = |l: &Lit| ((m >> (*l.var() - 1)) & 1 == 1) == l.polarity();
+            if cnf[1..].iter().all(|c| c.iter().any(v)) {
+                models.insert(proj.iter().map(|p| v(&p.lit())).collect::<Vec<_>>());
+            }
+        }
+        models.len() as u128
+    }
+
+    #[test]
+    fn test_exact_count() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..200 {
+            let num_var = rng.random_range(1..=10);
+            let mut cnf = Cnf::new();
+            cnf.new_var_to(Var(num_var));
+            for _ in 0..rng.random_range(0..=20) {
+                let c: LitVec = (0..rng.random_range(1..=3))
+                    .map(|_| Lit::new(Var(rng.random_range(1..=num_var)), rng.random()))
+                    .collect();
+                cnf.add_clause(&c);
+            }
+            let all: Vec<Var> = (1..=num_var).map(Var).collect();
+            assert_eq!(
+                ExactCounter::new(&cnf).count(),
+                Some(brute_force(num_var, &cnf, &all))
+            );
+            let proj: Vec<Var> = all.iter().copied().filter(|_| rng.random()).collect();
+            assert_eq!(
+                ExactCounter::new(&cnf).with_projection(&proj).count(),
+                Some(brute_force(num_var, &cnf, &proj))
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_overflow() {
+        let mut cnf = Cnf::new();
+        cnf.new_var_to(Var(130));
+        let x: Vec<Lit> = (0..=130).map(|v| Var(v).lit()).collect();
+        cnf.add_clause(&[x[1], x[2]]);
+        assert_eq!(ExactCounter::new(&cnf).count(), None);
+        let proj: Vec<Var> = (1..=128).map(Var).collect();
+        assert_eq!(
+            ExactCounter::new(&cnf).with_projection(&proj).count(),
+            Some(3 << 126)
+        );
+        let proj: Vec<Var> = (1..=129).map(Var).collect();
+        assert_eq!(ExactCounter::new(&cnf).with_projection(&proj).count(), None);
+        for c in [[x[1], !x[2]], [!x[1], x[2]], [!x[1], !x[2]]] {
+            cnf.add_clause(&c);
+        }
+        assert_eq!(ExactCounter::new(&cnf).count(), Some(0));
+    }
+
+    #[test]
+    fn test_approx_count() {
+        let mut cnf = Cnf::new();
+        cnf.new_var_to(Var(8));
+        let x: Vec<Lit> = (1..=8).map(|v| Var(v).lit()).collect();
+        cnf.add_clause(&[x[0], x[1], x[2]]);
+        cnf.add_clause(&[!x[3], x[4]]);
+        let exact = ExactCounter::new(&cnf).count().unwrap();
+        assert_eq!(exact, 256 / 8 * 7 / 4 * 3);
+        let approx = ApproxCounter::new(&cnf)
+            .with_tolerance(0.8, 0.5)
+            .count()
+            .unwrap();
+        assert!(approx as f64 >= exact as f64 / 1.8 && approx as f64 <= exact as f64 * 1.8);
+        let proj = &[Var(1), Var(2), Var(3)];
+        assert_eq!(
+            ApproxCounter::new(&cnf).with_projection(proj).count(),
+            Some(7)
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use super::DagCnf;
+use crate::Lit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CardEncoding {
+    SeqCounter,
+    #[default]
+    Totalizer,
+    SortingNetwork,
+}
+
+impl DagCnf {
+    /// `x & y` without creating a node when an operand is constant or both are
+    /// equal.
+    pub(crate) fn fold_and(&mut self, x: Lit, y: Lit) -> Lit {
+        match (x.try_constant(), y.try_constant()) {
+            (Some(false), _) | (_, Some(false)) => Lit::constant(false),
+            (Some(true), _) => y,
+            (_, Some(true)) => x,
+            _ if x == y => x,
+            _ if x == !y => Lit::constant(false),
+            _ => self.new_and([x, y]),
+        }
+    }
+
+    pub(crate) fn fold_or(&mut self, x: Lit, y: Lit) -> Lit {
+        !self.fold_and(!x, !y)
+    }
+
+    pub(crate) fn fold_xor(&mut self, x: Lit, y: Lit) -> Lit {
+        match (x.try_constant(), y.try_constant()) {
+            (Some(c), _) => y.not_if(c),
+            (_, Some(c)) => x.not_if(c),
+            _ if x == y => Lit::constant(false),
+            _ if x == !y => Lit::constant(true),
+            _ => self.new_xor(x, y),
+        }
+    }
+
+    pub(crate) fn fold_ite(&mut self, c: Lit, t: Lit, e: Lit) -> Lit {
+        match (c.try_constant(), t.try_constant(), e.try_constant()) {
+            (Some(c), _, _) => {
+                if c {
+                    t
+                } else {
+                    e
+                }
+            }
+            _ if t == e => t,
+            (_, Some(true), Some(false)) => c,
+            (_, Some(false), Some(true)) => !c,
+            (_, _, Some(false)) => self.fold_and(c, t),
+            (_, Some(false), _) => self.fold_and(!c, e),
+            (_, _, Some(true)) => self.fold_or(!c, t),
+            (_, Some(true), _) => self.fold_or(c, e),
+            _ => self.new_ite(c, t, e),
+        }
+    }
+
+    /// Unary representation of the number of true `lits`: the `i`-th output is
+    /// true iff at least `i + 1` of them are true. Only the first `bound`
+    /// outputs are built.
+    pub fn new_unary_count(&mut self, lits: &[Lit], bound: usize, enc: CardEncoding) -> Vec<Lit> {
+        let bound = bound.min(lits.len());
+        match enc {
+            CardEncoding::SeqCounter => self.seq_counter(lits, bound),
+            CardEncoding::Totalizer => Totalizer::new(self, lits, bound).outputs().to_vec(),
+            CardEncoding::SortingNetwork => {
+                let mut sorted = lits.to_vec();
+                self.sorting_network(&mut sorted);
+                sorted.truncate(bound);
+                sorted
+            }
+        }
+    }
+
+    /// A literal that is true iff at least `k` of `lits` are true.
+    pub fn new_at_least(&mut self, lits: &[Lit], k: usize, enc: CardEncoding) -> Lit {
+        if k == 0 {
+            return Lit::constant(true);
+        }
+        if k > lits.len() {
+            return Lit::constant(false);
+        }
+        self.new_unary_count(lits, k, enc)[k - 1]
+    }
+
+    #[inline]
+    pub fn new_at_most(&mut self, lits: &[Lit], k: usize, enc: CardEncoding) -> Lit {
+        !self.new_at_least(lits, k + 1, enc)
+    }
+
+    pub fn new_exactly(&mut self, lits: &[Lit], k: usize, enc: CardEncoding) -> Lit {
+        if k > lits.len() {
+            return Lit::constant(false);
+        }
+        let out = self.new_unary_count(lits, k + 1, enc);
+        let ge = if k == 0 {
+            Lit::constant(true)
+        } else {
+            out[k - 1]
+        };
+        let gt = out.get(k).copied().unwrap_or(Lit::constant(false));
+        self.fold_and(ge, !gt)
+    }
+
+    fn seq_counter(&mut self, lits: &[Lit], bound: usize) -> Vec<Lit> {
+        let mut s: Vec<Lit> = Vec::with_capacity(bound);
+        for &x in lits {
+            let mut prev = Lit::constant(true);
+            for j in 0..bound {
+                let cur = s.get(j).copied().unwrap_or(Lit::constant(false));
+                let inc = self.fold_and(x, prev);
+                prev = cur;
+                let n = self.fold_or(cur, inc);
+                if j < s.len() {
+                    s[j] = n;
+                } else {
+                    s.push(n);
+                }
+            }
+        }
+        s
+    }
+
+    fn comparator(&mut self, lits: &mut [Lit], i: usize, j: usize) {
+        let (x, y) = (lits[i], lits[j]);
+        lits[i] = self.fold_or(x, y);
+        lits[j] = self.fold_and(x, y);
+    }
+
+    /// Sorts `lits` descending with Batcher's odd-even merge sort.
+    fn sorting_network(&mut self, lits: &mut Vec<Lit>) {
+        let len = lits.len();
+        let n = len.next_power_of_two();
+        lits.resize(n, Lit::constant(false));
+        let mut p = 1;
+        while p < n {
+            let mut k = p;
+            while k >= 1 {
+                for j in (k % p..n - k).step_by(2 * k) {
+                    for i in 0..k.min(n - j - k) {
+                        if (i + j) / (2 * p) == (i + j + k) / (2 * p) {
+                            self.comparator(lits, i + j, i + j + k);
+                        }
+                    }
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+        lits.truncate(len);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TotalizerNode {
+    children: Option<(usize, usize)>,
+    size: usize,
+    out: Vec<Lit>,
+}
+
+/// Totalizer over `DagCnf` whose outputs can be extended incrementally, both
+/// by raising the bound and by adding inputs, so a bound can be tightened or
+/// relaxed without re-encoding.
+#[derive(Debug, Clone)]
+pub struct Totalizer {
+    nodes: Vec<TotalizerNode>,
+    root: Option<usize>,
+    bound: usize,
+}
+
+impl Totalizer {
+    pub fn new(dc: &mut DagCnf, lits: &[Lit], bound: usize) -> Self {
+        let mut res = Self {
+            nodes: Vec::new(),
+            root: None,
+            bound,
+        };
+        res.add_inputs(dc, lits);
+        res
+    }
+
+    fn build(&mut self, dc: &mut DagCnf, lits: &[Lit]) -> usize {
+        let node = if lits.len() == 1 {
+            TotalizerNode {
+                children: None,
+                size: 1,
+                out: lits.to_vec(),
+            }
+        } else {
+            let (l, r) = lits.split_at(lits.len() / 2);
+            let l = self.build(dc, l);
+            let r = self.build(dc, r);
+            self.merge(dc, l, r)
+        };
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn merge(&mut self, dc: &mut DagCnf, l: usize, r: usize) -> TotalizerNode {
+        let mut node = TotalizerNode {
+            children: Some((l, r)),
+            size: self.nodes[l].size + self.nodes[r].size,
+            out: Vec::new(),
+        };
+        self.extend_node(dc, &mut node);
+        node
+    }
+
+    fn extend_node(&mut self, dc: &mut DagCnf, node: &mut TotalizerNode) {
+        let Some((l, r)) = node.children else {
+            return;
+        };
+        let target = self.bound.min(node.size);
+        for id in [l, r] {
+            let mut child = self.nodes[id].clone();
+            self.extend_node(dc, &mut child);
+            self.nodes[id] = child;
+        }
+        let get = |out: &[Lit], i: usize| {
+            if i == 0 {
+                Lit::constant(true)
+            } else {
+                out.get(i - 1).copied().unwrap_or(Lit::constant(false))
+            }
+        };
+        for k in node.out.len() + 1..=target {
+            let mut o = Lit::constant(false);
+            for a in 0..=k {
+                let x = get(&self.nodes[l].out, a);
+                let y = get(&self.nodes[r].out, k - a);
+                let t = dc.fold_and(x, y);
+                o = dc.fold_or(o, t);
+            }
+            node.out.push(o);
+        }
+    }
+
+    /// Adds inputs, the new root merges the old tree with a tree over `lits`.
+    pub fn add_inputs(&mut self, dc: &mut DagCnf, lits: &[Lit]) {
+        if lits.is_empty() {
+            return;
+        }
+        let new = self.build(dc, lits);
+        self.root = Some(match self.root {
+            None => new,
+            Some(old) => {
+                let node = self.merge(dc, old, new);
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        });
+    }
+
+    /// Builds the outputs up to `bound`.
+    pub fn extend_bound(&mut self, dc: &mut DagCnf, bound: usize) {
+        if bound <= self.bound {
+            return;
+        }
+        self.bound = bound;
+        if let Some(root) = self.root {
+            let mut node = self.nodes[root].clone();
+            self.extend_node(dc, &mut node);
+            self.nodes[root] = node;
+        }
+    }
+
+    #[inline]
+    pub fn num_input(&self) -> usize {
+        self.root.map_or(0, |r| self.nodes[r].size)
+    }
+
+    /// The built outputs, the `i`-th is true iff at least `i + 1` inputs are
+    /// true.
+    #[inline]
+    pub fn outputs(&self) -> &[Lit] {
+        self.root.map_or(&[], |r| &self.nodes[r].out)
+    }
+
+    /// At least `k` inputs are true, `k` must be within the bound.
+    pub fn at_least(&self, k: usize) -> Lit {
+        if k == 0 {
+            return Lit::constant(true);
+        }
+        if k > self.num_input() {
+            return Lit::constant(false);
+        }
+        assert!(k <= self.bound, "totalizer bound {} exceeded", self.bound);
+        self.outputs()[k - 1]
+    }
+
+    #[inline]
+    pub fn at_most(&self, k: usize) -> Lit {
+        !self.at_least(k + 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dagcnf::test_util::truth_table;
+
+    #[test]
+    fn test_card() {
+        for n in 0..=6usize {
+            let mut dc = DagCnf::new();
+            let x: Vec<Lit> = (0..n).map(|_| dc.new_var().lit()).collect();
+            let mut outs = Vec::new();
+            for enc in [
+                CardEncoding::SeqCounter,
+                CardEncoding::Totalizer,
+                CardEncoding::SortingNetwork,
+            ] {
+                for k in 0..=n + 1 {
+                    outs.push((0, k, dc.new_at_least(&x, k, enc)));
+                    outs.push((1, k, dc.new_at_most(&x, k, enc)));
+                    outs.push((2, k, dc.new_exactly(&x, k, enc)));
+                }
+            }
+            let mut tot = Totalizer::new(&mut dc, &x[..n / 2], 1);
+            tot.add_inputs(&mut dc, &x[n / 2..]);
+            tot.extend_bound(&mut dc, n);
+            for k in 0..=n {
+                outs.push((0, k, tot.at_least(k)));
+            }
+            let lits: Vec<Lit> = outs.iter().map(|o| o.2).collect();
+            for (m, val) in truth_table(&dc, &x, &lits).into_iter().enumerate() {
+                let c = m.count_ones() as usize;
+                for (&(t, k, _), v) in outs.iter().zip(val) {
+                    assert_eq!(v, [c >= k, c <= k, c == k][t]);
+                }
+            }
+        }
+    }
+}
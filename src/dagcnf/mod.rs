@@ -1,8 +1,16 @@
+mod card;
+mod pb;
 mod replace;
 pub mod simplify;
 pub mod simulate;
 
-use crate::{Lit, LitVec, LitVvec, Var, VarMap, VarVMap};
+#[cfg(test)]
+pub(crate) mod test_util;
+
+pub use card::*;
+pub use pb::*;
+
+use crate::{Lit, LitVec, LitVvec, Var, VarAssign, VarMap, VarVMap};
 use giputils::hash::GHashSet;
 use std::{
     fmt::Display,
@@ -71,6 +79,11 @@ impl DagCnf {
         self.cnf.iter().flatten()
     }
 
+    #[inline]
+    pub fn check_model(&self, model: &VarAssign) -> Result<(), &LitVec> {
+        model.check(self.clause())
+    }
+
     #[inline]
     pub fn dep(&self, n: Var) -> &[Var] {
         &self.dep[n]
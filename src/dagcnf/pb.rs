@@ -0,0 +1,188 @@
+use super::DagCnf;
+use crate::Lit;
+use giputils::hash::GHashMap;
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PbEncoding {
+    /// Binary adder network compared against the bound.
+    Adder,
+    /// Reduced BDD built with `new_ite`.
+    #[default]
+    Bdd,
+}
+
+impl DagCnf {
+    /// A literal that is true iff `sum(w * l) >= k` over `terms`.
+    #[inline]
+    pub fn new_pb_at_least(&mut self, terms: &[(Lit, u64)], k: u64, enc: PbEncoding) -> Lit {
+        self.pb_at_least(terms, k as u128, enc)
+    }
+
+    #[inline]
+    pub fn new_pb_at_most(&mut self, terms: &[(Lit, u64)], k: u64, enc: PbEncoding) -> Lit {
+        !self.pb_at_least(terms, k as u128 + 1, enc)
+    }
+
+    pub fn new_pb_equal(&mut self, terms: &[(Lit, u64)], k: u64, enc: PbEncoding) -> Lit {
+        let ge = self.new_pb_at_least(terms, k, enc);
+        let le = self.new_pb_at_most(terms, k, enc);
+        self.fold_and(ge, le)
+    }
+
+    /// The sums are taken in `u128`, which no sum of `u64` weights overflows.
+    fn pb_at_least(&mut self, terms: &[(Lit, u64)], k: u128, enc: PbEncoding) -> Lit {
+        let terms: Vec<(Lit, u64)> = terms.iter().copied().filter(|(_, w)| *w > 0).collect();
+        let total: u128 = terms.iter().map(|(_, w)| *w as u128).sum();
+        if k == 0 {
+            return Lit::constant(true);
+        }
+        if k > total {
+            return Lit::constant(false);
+        }
+        match enc {
+            PbEncoding::Adder => self.pb_adder(&terms, k),
+            PbEncoding::Bdd => {
+                let mut terms = terms;
+                terms.sort_by_key(|t| Reverse(t.1));
+                let mut suffix = vec![0; terms.len() + 1];
+                for i in (0..terms.len()).rev() {
+                    suffix[i] = suffix[i + 1] + terms[i].1 as u128;
+                }
+                self.pb_bdd(&terms, &suffix, 0, k, &mut GHashMap::new())
+            }
+        }
+    }
+
+    fn pb_bdd(
+        &mut self,
+        terms: &[(Lit, u64)],
+        suffix: &[u128],
+        i: usize,
+        k: u128,
+        memo: &mut GHashMap<(usize, u128), Lit>,
+    ) -> Lit {
+        if k == 0 {
+            return Lit::constant(true);
+        }
+        if suffix[i] < k {
+            return Lit::constant(false);
+        }
+        if let Some(l) = memo.get(&(i, k)) {
+            return *l;
+        }
+        let (x, w) = terms[i];
+        let t = self.pb_bdd(terms, suffix, i + 1, k.saturating_sub(w as u128), memo);
+        let e = self.pb_bdd(terms, suffix, i + 1, k, memo);
+        let res = self.fold_ite(x, t, e);
+        memo.insert((i, k), res);
+        res
+    }
+
+    /// Returns `(sum, carry)` of the full adder over `x`, `y` and `z`.
+    fn full_adder(&mut self, x: Lit, y: Lit, z: Lit) -> (Lit, Lit) {
+        let xy = self.fold_xor(x, y);
+        let sum = self.fold_xor(xy, z);
+        let a = self.fold_and(x, y);
+        let b = self.fold_and(xy, z);
+        (sum, self.fold_or(a, b))
+    }
+
+    fn pb_adder(&mut self, terms: &[(Lit, u64)], k: u128) -> Lit {
+        let total: u128 = terms.iter().map(|(_, w)| *w as u128).sum();
+        let width = (u128::BITS - total.leading_zeros()) as usize;
+        let mut buckets: Vec<Vec<Lit>> = vec![Vec::new(); width + 1];
+        for &(x, w) in terms {
+            for (j, b) in buckets.iter_mut().enumerate().take(u64::BITS as usize) {
+                if (w >> j) & 1 == 1 {
+                    b.push(x);
+                }
+            }
+        }
+        let mut sum = Vec::with_capacity(width);
+        for j in 0..width {
+            while buckets[j].len() > 1 {
+                let x = buckets[j].pop().unwrap();
+                let y = buckets[j].pop().unwrap();
+                let z = buckets[j].pop().unwrap_or(Lit::constant(false));
+                let (s, c) = self.full_adder(x, y, z);
+                buckets[j].push(s);
+                buckets[j + 1].push(c);
+            }
+            sum.push(buckets[j].pop().unwrap_or(Lit::constant(false)));
+        }
+        let mut ge = Lit::constant(true);
+        for (j, &s) in sum.iter().enumerate() {
+            ge = if (k >> j) & 1 == 1 {
+                self.fold_and(s, ge)
+            } else {
+                self.fold_or(s, ge)
+            };
+        }
+        ge
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dagcnf::test_util::truth_table;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn test_pb() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let n = rng.random_range(1..=6);
+            let mut dc = DagCnf::new();
+            let x: Vec<Lit> = (0..n).map(|_| dc.new_var().lit()).collect();
+            let terms: Vec<(Lit, u64)> = x.iter().map(|l| (*l, rng.random_range(0..=7))).collect();
+            let total: u64 = terms.iter().map(|t| t.1).sum();
+            let mut outs = Vec::new();
+            for enc in [PbEncoding::Adder, PbEncoding::Bdd] {
+                for k in 0..=total + 1 {
+                    outs.push((0, k, dc.new_pb_at_least(&terms, k, enc)));
+                    outs.push((1, k, dc.new_pb_at_most(&terms, k, enc)));
+                    outs.push((2, k, dc.new_pb_equal(&terms, k, enc)));
+                }
+            }
+            let lits: Vec<Lit> = outs.iter().map(|o| o.2).collect();
+            for (m, val) in truth_table(&dc, &x, &lits).into_iter().enumerate() {
+                let s: u64 = (0..n)
+                    .filter(|i| (m >> i) & 1 == 1)
+                    .map(|i| terms[i].1)
+                    .sum();
+                for (&(t, k, _), v) in outs.iter().zip(val) {
+                    assert_eq!(v, [s >= k, s <= k, s == k][t]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pb_large_weights() {
+        let mut dc = DagCnf::new();
+        let x: Vec<Lit> = (0..3).map(|_| dc.new_var().lit()).collect();
+        let terms = [(x[0], u64::MAX), (x[1], u64::MAX - 1), (x[2], 2)];
+        let ks = [0, 1, 2, u64::MAX - 1, u64::MAX];
+        let mut outs = Vec::new();
+        for enc in [PbEncoding::Adder, PbEncoding::Bdd] {
+            for k in ks {
+                outs.push((0, k, dc.new_pb_at_least(&terms, k, enc)));
+                outs.push((1, k, dc.new_pb_at_most(&terms, k, enc)));
+                outs.push((2, k, dc.new_pb_equal(&terms, k, enc)));
+            }
+        }
+        let lits: Vec<Lit> = outs.iter().map(|o| o.2).collect();
+        for (m, val) in truth_table(&dc, &x, &lits).into_iter().enumerate() {
+            let s: u128 = (0..3)
+                .filter(|i| (m >> i) & 1 == 1)
+                .map(|i| terms[i].1 as u128)
+                .sum();
+            for (&(t, k, _), v) in outs.iter().zip(val) {
+                let k = k as u128;
+                assert_eq!(v, [s >= k, s <= k, s == k][t], "{m} {t} {k}");
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{
+    DagCnf, Lit,
+    satif::{CdclSolver, Satif},
+};
+
+/// Values of `outs` under every assignment to `inputs`.
+pub(crate) fn truth_table(dc: &DagCnf, inputs: &[Lit], outs: &[Lit]) -> Vec<Vec<bool>> {
+    let mut solver = CdclSolver::new();
+    for c in dc.clause() {
+        solver.add_clause(c);
+    }
+    (0..1u32 << inputs.len())
+        .map(|m| {
+            let assumps: Vec<Lit> = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, l)| l.not_if((m >> i) & 1 == 0))
+                .collect();
+            assert!(solver.solve(&assumps));
+            outs.iter().map(|o| solver.sat_value(*o).unwrap()).collect()
+        })
+        .collect()
+}
@@ -1,55 +1,127 @@
-use crate::{Lit, LitVec, Var};
+use crate::{Cnf, Lit, LitVec, Var};
+use flate2::{Compression, read::MultiGzDecoder, write::GzEncoder};
 use std::{
-    fs::{File, read_to_string},
-    io::Write,
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
     path::Path,
 };
+use xz2::{read::XzDecoder, write::XzEncoder};
 
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Opens a file for reading, transparently decompressing gzip or xz input
+/// detected by its magic bytes.
+pub fn open_compressed<P: AsRef<Path>>(file: P) -> io::Result<Box<dyn BufRead>> {
+    let mut file = BufReader::new(File::open(file)?);
+    let magic = file.fill_buf()?;
+    Ok(if magic.starts_with(GZIP_MAGIC) {
+        Box::new(BufReader::new(MultiGzDecoder::new(file)))
+    } else if magic.starts_with(XZ_MAGIC) {
+        Box::new(BufReader::new(XzDecoder::new_multi_decoder(file)))
+    } else {
+        Box::new(file)
+    })
+}
+
+/// A file writer returned by [`create_compressed`]. Call [`finish`] to
+/// write the compression trailer, dropping it discards any error.
+///
+/// [`finish`]: CompressedWriter::finish
+pub enum CompressedWriter {
+    Plain(BufWriter<File>),
+    Gz(GzEncoder<BufWriter<File>>),
+    Xz(XzEncoder<BufWriter<File>>),
+}
+
+impl CompressedWriter {
+    /// Finishes the compressed stream and flushes the file.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            CompressedWriter::Plain(mut w) => w.flush(),
+            CompressedWriter::Gz(w) => w.finish()?.flush(),
+            CompressedWriter::Xz(w) => w.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for CompressedWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CompressedWriter::Plain(w) => w.write(buf),
+            CompressedWriter::Gz(w) => w.write(buf),
+            CompressedWriter::Xz(w) => w.write(buf),
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CompressedWriter::Plain(w) => w.flush(),
+            CompressedWriter::Gz(w) => w.flush(),
+            CompressedWriter::Xz(w) => w.flush(),
+        }
+    }
+}
+
+/// Creates a file for writing, compressing the output with gzip or xz when
+/// the file name ends with `.gz` or `.xz`.
+pub fn create_compressed<P: AsRef<Path>>(file: P) -> io::Result<CompressedWriter> {
+    let ext = file.as_ref().extension().and_then(|e| e.to_str());
+    let out = BufWriter::new(File::create(file.as_ref())?);
+    Ok(match ext {
+        Some("gz") => CompressedWriter::Gz(GzEncoder::new(out, Compression::default())),
+        Some("xz") => CompressedWriter::Xz(XzEncoder::new(out, 6)),
+        _ => CompressedWriter::Plain(out),
+    })
+}
+
+/// Reads the clauses of a DIMACS file, panicking on invalid input. Use
+/// [`read_dimacs_file`] to handle errors.
 pub fn from_dimacs_file<P: AsRef<Path>>(file: P) -> Vec<LitVec> {
-    let str = read_to_string(file).unwrap();
-    from_dimacs_str(&str)
+    let file = open_compressed(file).unwrap();
+    DimacsReader::new(file)
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| panic!("{e}"))
 }
 
+/// Reads the clauses of a DIMACS string, panicking on invalid input. Use
+/// [`read_dimacs_str`] to handle errors.
 pub fn from_dimacs_str(str: &str) -> Vec<LitVec> {
-    let mut cnf = Vec::new();
-    for line in str.lines() {
-        if line.is_empty() || &line[0..1] == "p" || &line[0..1] == "c" {
-            continue;
-        }
-        let mut clause: Vec<i32> = line
-            .split_whitespace()
-            .map(|s| s.parse::<i32>().unwrap())
-            .collect();
-        assert!(clause.pop().unwrap() == 0);
-        cnf.push(LitVec::from_iter(clause.into_iter().map(|lit| {
-            Lit::new(Var::new((lit.unsigned_abs() - 1) as _), lit > 0)
-        })));
-    }
-    cnf
+    DimacsReader::new(str.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| panic!("{e}"))
 }
 
+/// Writes `cnf` with variable `Var(k)` as DIMACS variable `k`. Constant
+/// literals are simplified away: a clause with `Lit::constant(true)` is
+/// dropped and `Lit::constant(false)` is removed from its clause.
 pub fn to_dimacs(cnf: &[LitVec]) -> String {
-    let mut max_var = 0;
-    for cls in cnf.iter() {
-        max_var = max_var.max(
+    let cnf: Vec<Vec<Lit>> = cnf
+        .iter()
+        .filter(|cls| !cls.contains(&Lit::constant(true)))
+        .map(|cls| {
             cls.iter()
-                .map(|l| Into::<usize>::into(l.var()))
-                .max()
-                .unwrap(),
-        );
-    }
-    max_var += 1;
+                .copied()
+                .filter(|l| !l.var().is_constant())
+                .collect()
+        })
+        .collect();
+    let max_var = cnf
+        .iter()
+        .flatten()
+        .map(|l| l.var())
+        .max()
+        .unwrap_or(Var::CONST);
     let mut dimacs = Vec::new();
     dimacs.push(format!("p cnf {max_var} {}", cnf.len()));
-    for cls in cnf.iter() {
+    for cls in cnf {
         let mut s = String::new();
-        for l in cls.iter().map(|l| {
-            let mut res = Into::<usize>::into(l.var()) as i32 + 1;
-            if !l.polarity() {
-                res = -res;
-            }
-            res
-        }) {
+        for l in cls.iter() {
             s.push_str(&format!("{l} "));
         }
         s.push('0');
@@ -60,6 +132,399 @@ pub fn to_dimacs(cnf: &[LitVec]) -> String {
 
 pub fn to_dimacs_file<P: AsRef<Path>>(cnf: &[LitVec], file: P) {
     let dimacs = to_dimacs(cnf);
-    let mut file = File::create(file).unwrap();
+    let mut file = create_compressed(file).unwrap();
     file.write_all(dimacs.as_bytes()).unwrap();
+    file.finish().unwrap();
+}
+
+#[derive(Debug)]
+pub enum DimacsErrorKind {
+    Io(io::Error),
+    InvalidHeader,
+    DuplicateHeader,
+    HeaderAfterClause,
+    InvalidLiteral(String),
+    InvalidWeight(String),
+    InvalidConstraint(String),
+    VarOutOfRange { var: u32, num_var: u32 },
+    ClauseCountMismatch { expected: usize, found: usize },
+    UnterminatedClause,
+}
+
+#[derive(Debug)]
+pub struct DimacsError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: DimacsErrorKind,
+}
+
+impl DimacsError {
+    #[inline]
+    pub(crate) fn new(line: usize, column: usize, kind: DimacsErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dimacs {}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            DimacsErrorKind::Io(e) => write!(f, "io error: {e}"),
+            DimacsErrorKind::InvalidHeader => {
+                write!(f, "invalid header, expect `p cnf <vars> <clauses>`")
+            }
+            DimacsErrorKind::DuplicateHeader => write!(f, "duplicate header"),
+            DimacsErrorKind::HeaderAfterClause => write!(f, "header after clauses"),
+            DimacsErrorKind::InvalidLiteral(t) => write!(f, "invalid literal `{t}`"),
+            DimacsErrorKind::InvalidWeight(t) => write!(f, "invalid weight `{t}`"),
+            DimacsErrorKind::InvalidConstraint(t) => write!(f, "invalid constraint at `{t}`"),
+            DimacsErrorKind::VarOutOfRange { var, num_var } => {
+                write!(f, "variable {var} exceeds declared {num_var} variables")
+            }
+            DimacsErrorKind::ClauseCountMismatch { expected, found } => {
+                write!(f, "header declares {expected} clauses, found {found}")
+            }
+            DimacsErrorKind::UnterminatedClause => write!(f, "clause not terminated by 0"),
+        }
+    }
+}
+
+impl Error for DimacsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            DimacsErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Streaming DIMACS CNF reader. DIMACS variable `k` is mapped to `Var(k)`, as
+/// in [`to_dimacs`] and [`from_dimacs_str`], so `Var::CONST` is never produced
+/// by the input.
+pub struct DimacsReader<R: BufRead> {
+    reader: R,
+    buf: String,
+    line: usize,
+    header: Option<(u32, usize)>,
+    clause: LitVec,
+    clause_pos: (usize, usize),
+    ready: VecDeque<LitVec>,
+    num_clause: usize,
+    eof: bool,
+}
+
+impl<R: BufRead> DimacsReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+            header: None,
+            clause: LitVec::new(),
+            clause_pos: (0, 0),
+            ready: VecDeque::new(),
+            num_clause: 0,
+            eof: false,
+        }
+    }
+
+    /// The `(num_var, num_clause)` declared by the `p cnf` header, if it has been read.
+    #[inline]
+    pub fn header(&self) -> Option<(usize, usize)> {
+        self.header.map(|(v, c)| (v as usize, c))
+    }
+
+    /// Reads lines until the header or the first clause is found.
+    pub fn read_header(&mut self) -> Result<Option<(usize, usize)>, DimacsError> {
+        while self.header.is_none() && self.ready.is_empty() && self.clause.is_empty() && !self.eof
+        {
+            self.read_line()?;
+        }
+        Ok(self.header())
+    }
+
+    pub fn read_cnf(mut self) -> Result<Cnf, DimacsError> {
+        let mut cnf = Cnf::new();
+        if let Some((num_var, _)) = self.read_header()? {
+            cnf.new_var_to(Var::new(num_var));
+        }
+        for cls in self.by_ref() {
+            cnf.add_clause(&cls?);
+        }
+        Ok(cnf)
+    }
+
+    fn parse_header(&mut self, tokens: &[(usize, &str)]) -> Result<(), DimacsError> {
+        let line = self.line;
+        let col = tokens[0].0;
+        if self.header.is_some() {
+            return Err(DimacsError::new(
+                line,
+                col,
+                DimacsErrorKind::DuplicateHeader,
+            ));
+        }
+        if self.num_clause > 0 || !self.clause.is_empty() {
+            return Err(DimacsError::new(
+                line,
+                col,
+                DimacsErrorKind::HeaderAfterClause,
+            ));
+        }
+        if tokens.len() != 4 || tokens[0].1 != "p" || tokens[1].1 != "cnf" {
+            return Err(DimacsError::new(line, col, DimacsErrorKind::InvalidHeader));
+        }
+        let num_var = tokens[2].1.parse::<u32>();
+        let num_clause = tokens[3].1.parse::<usize>();
+        match (num_var, num_clause) {
+            (Ok(v), Ok(c)) => {
+                self.header = Some((v, c));
+                Ok(())
+            }
+            (Err(_), _) => Err(DimacsError::new(
+                line,
+                tokens[2].0,
+                DimacsErrorKind::InvalidHeader,
+            )),
+            (_, Err(_)) => Err(DimacsError::new(
+                line,
+                tokens[3].0,
+                DimacsErrorKind::InvalidHeader,
+            )),
+        }
+    }
+
+    fn parse_lit(&self, col: usize, token: &str) -> Result<Option<Lit>, DimacsError> {
+        let invalid = || {
+            DimacsError::new(
+                self.line,
+                col,
+                DimacsErrorKind::InvalidLiteral(token.to_string()),
+            )
+        };
+        let lit: i64 = token.parse().map_err(|_| invalid())?;
+        if lit == 0 {
+            return Ok(None);
+        }
+        let var = u32::try_from(lit.unsigned_abs()).map_err(|_| invalid())?;
+        if var >= u32::MAX >> 1 {
+            return Err(invalid());
+        }
+        if let Some((num_var, _)) = self.header
+            && var > num_var
+        {
+            return Err(DimacsError::new(
+                self.line,
+                col,
+                DimacsErrorKind::VarOutOfRange { var, num_var },
+            ));
+        }
+        Ok(Some(Lit::new(Var(var), lit > 0)))
+    }
+
+    fn read_line(&mut self) -> Result<(), DimacsError> {
+        self.buf.clear();
+        let n = self
+            .reader
+            .read_line(&mut self.buf)
+            .map_err(|e| DimacsError::new(self.line + 1, 0, DimacsErrorKind::Io(e)))?;
+        if n == 0 {
+            return self.finish();
+        }
+        self.line += 1;
+        let buf = std::mem::take(&mut self.buf);
+        let res = self.parse_line(&buf);
+        self.buf = buf;
+        res
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), DimacsError> {
+        let tokens: Vec<(usize, &str)> = line
+            .split_ascii_whitespace()
+            .map(|t| (t.as_ptr() as usize - line.as_ptr() as usize + 1, t))
+            .collect();
+        let Some(&(_, first)) = tokens.first() else {
+            return Ok(());
+        };
+        if first.starts_with('c') {
+            return Ok(());
+        }
+        if first.starts_with('%') {
+            return self.finish();
+        }
+        if first.starts_with('p') {
+            return self.parse_header(&tokens);
+        }
+        for &(col, token) in tokens.iter() {
+            if self.clause.is_empty() {
+                self.clause_pos = (self.line, col);
+            }
+            match self.parse_lit(col, token)? {
+                Some(lit) => self.clause.push(lit),
+                None => {
+                    self.num_clause += 1;
+                    self.ready.push_back(std::mem::take(&mut self.clause));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), DimacsError> {
+        self.eof = true;
+        if !self.clause.is_empty() {
+            let (line, col) = self.clause_pos;
+            self.clause.clear();
+            return Err(DimacsError::new(
+                line,
+                col,
+                DimacsErrorKind::UnterminatedClause,
+            ));
+        }
+        if let Some((_, expected)) = self.header
+            && expected != self.num_clause
+        {
+            return Err(DimacsError::new(
+                self.line,
+                0,
+                DimacsErrorKind::ClauseCountMismatch {
+                    expected,
+                    found: self.num_clause,
+                },
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for DimacsReader<R> {
+    type Item = Result<LitVec, DimacsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(cls) = self.ready.pop_front() {
+                return Some(Ok(cls));
+            }
+            if self.eof {
+                return None;
+            }
+            if let Err(e) = self.read_line() {
+                self.eof = true;
+                self.ready.clear();
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+pub fn read_dimacs<R: BufRead>(reader: R) -> Result<Cnf, DimacsError> {
+    DimacsReader::new(reader).read_cnf()
+}
+
+pub fn read_dimacs_str(str: &str) -> Result<Cnf, DimacsError> {
+    read_dimacs(str.as_bytes())
+}
+
+pub fn read_dimacs_file<P: AsRef<Path>>(file: P) -> Result<Cnf, DimacsError> {
+    let file = open_compressed(file).map_err(|e| DimacsError::new(0, 0, DimacsErrorKind::Io(e)))?;
+    read_dimacs(file)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reader() {
+        let cnf = read_dimacs_str("c comment\np cnf 3 3\n1 -2\n 3 0 -1 0\n2\n3 0\n").unwrap();
+        assert_eq!(cnf.max_var(), Var(3));
+        assert_eq!(
+            &cnf[1..],
+            &[
+                LitVec::from([Lit::from(1), Lit::from(-2), Lit::from(3)]),
+                LitVec::from([Lit::from(-1)]),
+                LitVec::from([Lit::from(2), Lit::from(3)]),
+            ]
+        );
+        let err = read_dimacs_str("p cnf 2 1\n1 -3 0\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(matches!(
+            err.kind,
+            DimacsErrorKind::VarOutOfRange { var: 3, num_var: 2 }
+        ));
+        let err = read_dimacs_str("p cnf 2 2\n1 x 0\n").unwrap_err();
+        assert!(matches!(err.kind, DimacsErrorKind::InvalidLiteral(_)));
+        let err = read_dimacs_str("p cnf 2 2\n1 2 0\n").unwrap_err();
+        assert!(matches!(
+            err.kind,
+            DimacsErrorKind::ClauseCountMismatch {
+                expected: 2,
+                found: 1
+            }
+        ));
+        let err = read_dimacs_str("1 2 0\n-1").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(matches!(err.kind, DimacsErrorKind::UnterminatedClause));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut cnf = Cnf::new();
+        cnf.add_clause(&[Lit::from(1), Lit::from(-2)]);
+        cnf.add_clause(&[Lit::from(3)]);
+        cnf.add_clause(&[Lit::from(-1), Lit::from(2), Lit::from(-3)]);
+        let dimacs = to_dimacs(&cnf);
+        assert!(dimacs.starts_with("p cnf 3 3\n1 -2 0\n"));
+        let read = read_dimacs_str(&dimacs).unwrap();
+        assert_eq!(read.max_var(), cnf.max_var());
+        assert_eq!(read.clauses(), cnf.clauses());
+        assert_eq!(from_dimacs_str(&dimacs), &cnf[1..]);
+        let constant = [
+            LitVec::from([Lit::from(1), Lit::constant(true)]),
+            LitVec::from([Lit::constant(false), Lit::from(-2)]),
+            LitVec::from([Lit::constant(false)]),
+        ];
+        assert_eq!(to_dimacs(&constant), "p cnf 2 2\n-2 0\n0");
+    }
+
+    #[test]
+    fn test_compressed() {
+        let mut cnf = Cnf::new();
+        cnf.add_clause(&[Lit::from(1), Lit::from(-2)]);
+        cnf.add_clause(&[Lit::from(3)]);
+        for (ext, magic) in [
+            ("cnf", &b"p cnf"[..]),
+            ("cnf.gz", GZIP_MAGIC),
+            ("cnf.xz", XZ_MAGIC),
+        ] {
+            let file = std::env::temp_dir().join(format!(
+                "logicrs_dimacs_test_compressed_{}.{ext}",
+                std::process::id()
+            ));
+            to_dimacs_file(&cnf, &file);
+            let raw = std::fs::read(&file).unwrap();
+            let legacy = from_dimacs_file(&file);
+            let read = read_dimacs_file(&file);
+            std::fs::remove_file(&file).unwrap();
+            assert!(raw.starts_with(magic), "{ext}");
+            assert_eq!(legacy, &cnf[1..], "{ext}");
+            assert_eq!(read.unwrap().clauses(), cnf.clauses(), "{ext}");
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_compressed_finish_error() {
+        for ext in ["gz", "xz"] {
+            let file = std::env::temp_dir().join(format!(
+                "logicrs_dimacs_test_finish_error_{}.cnf.{ext}",
+                std::process::id()
+            ));
+            std::os::unix::fs::symlink("/dev/full", &file).unwrap();
+            let mut out = create_compressed(&file).unwrap();
+            let res = out.write_all(b"p cnf 0 0\n").and_then(|_| out.finish());
+            std::fs::remove_file(&file).unwrap();
+            assert_eq!(res.unwrap_err().kind(), io::ErrorKind::StorageFull, "{ext}");
+        }
+    }
 }
@@ -6,7 +6,7 @@ use std::{iter::repeat_with, ops::Deref};
 impl BvConst {
     #[inline]
     pub fn bitblast(&self) -> TermVec {
-        self.iter().map(|c| Term::bool_const(*c)).collect()
+        self.iter().map(Term::bool_const).collect()
     }
 
     #[inline]
@@ -0,0 +1,617 @@
+use super::{
+    BvConst, Sort, Term, TermType,
+    op::{Concat, DynOp, Eq, Iff, Implies, Ite, Neq, Read, Sext, Slice, Write as WriteOp},
+};
+use crate::dimacs::{create_compressed, open_compressed};
+use giputils::hash::GHashMap;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{self, BufRead, Write},
+    ops::Deref,
+    path::Path,
+};
+
+/// A word-level transition system in BTOR2. `init` and `next` are keyed by
+/// the state variables in `latchs`.
+#[derive(Debug, Clone, Default)]
+pub struct Btor2 {
+    pub inputs: Vec<Term>,
+    pub latchs: Vec<Term>,
+    pub init: GHashMap<Term, Term>,
+    pub next: GHashMap<Term, Term>,
+    pub outputs: Vec<Term>,
+    pub bads: Vec<Term>,
+    pub constraints: Vec<Term>,
+    pub fairness: Vec<Term>,
+    pub justice: Vec<Vec<Term>>,
+    pub symbols: GHashMap<Term, String>,
+}
+
+impl Btor2 {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug)]
+pub enum Btor2ErrorKind {
+    Io(io::Error),
+    Syntax(String),
+    UnknownOp(String),
+    UndefinedNode(i64),
+    InvalidSort(String),
+    InvalidConst(String),
+    Unsupported(String),
+}
+
+#[derive(Debug)]
+pub struct Btor2Error {
+    pub line: usize,
+    pub kind: Btor2ErrorKind,
+}
+
+impl Display for Btor2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "btor2 {}: ", self.line)?;
+        match &self.kind {
+            Btor2ErrorKind::Io(e) => write!(f, "io error: {e}"),
+            Btor2ErrorKind::Syntax(t) => write!(f, "invalid line `{t}`"),
+            Btor2ErrorKind::UnknownOp(t) => write!(f, "unknown op `{t}`"),
+            Btor2ErrorKind::UndefinedNode(n) => write!(f, "undefined node {n}"),
+            Btor2ErrorKind::InvalidSort(t) => write!(f, "invalid sort `{t}`"),
+            Btor2ErrorKind::InvalidConst(t) => write!(f, "invalid constant `{t}`"),
+            Btor2ErrorKind::Unsupported(t) => write!(f, "unsupported {t}"),
+        }
+    }
+}
+
+impl Error for Btor2Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            Btor2ErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+struct Btor2Parser {
+    line: usize,
+    sorts: GHashMap<i64, Sort>,
+    nodes: GHashMap<i64, Term>,
+}
+
+impl Btor2Parser {
+    fn error(&self, kind: Btor2ErrorKind) -> Btor2Error {
+        Btor2Error {
+            line: self.line,
+            kind,
+        }
+    }
+
+    fn syntax(&self, t: &[&str]) -> Btor2Error {
+        self.error(Btor2ErrorKind::Syntax(t.join(" ")))
+    }
+
+    fn id(&self, t: &str) -> Result<i64, Btor2Error> {
+        t.parse()
+            .map_err(|_| self.error(Btor2ErrorKind::Syntax(t.to_string())))
+    }
+
+    fn num(&self, t: &str) -> Result<usize, Btor2Error> {
+        t.parse()
+            .map_err(|_| self.error(Btor2ErrorKind::Syntax(t.to_string())))
+    }
+
+    fn sort(&self, t: &str) -> Result<Sort, Btor2Error> {
+        let id = self.id(t)?;
+        self.sorts
+            .get(&id)
+            .copied()
+            .ok_or_else(|| self.error(Btor2ErrorKind::InvalidSort(t.to_string())))
+    }
+
+    fn node(&self, t: &str) -> Result<Term, Btor2Error> {
+        let id = self.id(t)?;
+        let n = i64::try_from(id.unsigned_abs())
+            .ok()
+            .and_then(|n| self.nodes.get(&n))
+            .ok_or_else(|| self.error(Btor2ErrorKind::UndefinedNode(id)))?;
+        if id > 0 {
+            Ok(n.clone())
+        } else if n.sort().is_array() {
+            Err(self.error(Btor2ErrorKind::InvalidSort(t.to_string())))
+        } else {
+            Ok(!n)
+        }
+    }
+
+    fn bv_node(&self, t: &str) -> Result<Term, Btor2Error> {
+        let n = self.node(t)?;
+        if n.sort().is_array() {
+            return Err(self.error(Btor2ErrorKind::InvalidSort(t.to_string())));
+        }
+        Ok(n)
+    }
+
+    fn bool_node(&self, t: &str) -> Result<Term, Btor2Error> {
+        let n = self.node(t)?;
+        if n.sort() != Sort::Bv(1) {
+            return Err(self.error(Btor2ErrorKind::InvalidSort(t.to_string())));
+        }
+        Ok(n)
+    }
+
+    fn const_node(&self, kind: &str, sort: Sort, t: Option<&str>) -> Result<Term, Btor2Error> {
+        let Sort::Bv(w) = sort else {
+            return Err(self.error(Btor2ErrorKind::InvalidSort(kind.to_string())));
+        };
+        let c = match (kind, t) {
+            ("zero", None) => Some(BvConst::zero(w)),
+            ("one", None) => Some(BvConst::one(w)),
+            ("ones", None) => Some(BvConst::ones(w)),
+            ("const", Some(t)) => BvConst::from_str_radix(t, 2, w),
+            ("consth", Some(t)) => BvConst::from_str_radix(t, 16, w),
+            ("constd", Some(t)) => BvConst::from_str_radix(t, 10, w),
+            _ => None,
+        };
+        let c = c.ok_or_else(|| {
+            self.error(Btor2ErrorKind::InvalidConst(t.unwrap_or(kind).to_string()))
+        })?;
+        Ok(Term::bv_const(c))
+    }
+
+    fn parse_line(&mut self, line: &str, btor: &mut Btor2) -> Result<(), Btor2Error> {
+        let line = line.split(';').next().unwrap();
+        let tokens: Vec<&str> = line.split_ascii_whitespace().collect();
+        if tokens.is_empty() {
+            return Ok(());
+        }
+        if tokens.len() < 2 {
+            return Err(self.syntax(&tokens));
+        }
+        let nid = self.id(tokens[0])?;
+        if nid <= 0 || self.nodes.contains_key(&nid) || self.sorts.contains_key(&nid) {
+            return Err(self.syntax(&tokens));
+        }
+        let kind = tokens[1];
+        let args = &tokens[2..];
+        let term = match (kind, args) {
+            ("sort", ["bitvec", w]) => {
+                let w = self.num(w)?;
+                if w == 0 {
+                    return Err(self.error(Btor2ErrorKind::InvalidSort(w.to_string())));
+                }
+                self.sorts.insert(nid, Sort::Bv(w));
+                return Ok(());
+            }
+            ("sort", ["array", i, e]) => {
+                let (Sort::Bv(i), Sort::Bv(e)) = (self.sort(i)?, self.sort(e)?) else {
+                    return Err(self.syntax(&tokens));
+                };
+                self.sorts.insert(nid, Sort::Array(i, e));
+                return Ok(());
+            }
+            ("input" | "state", [s, sym @ ..]) => {
+                let t = Term::new_var(self.sort(s)?);
+                if kind == "input" {
+                    btor.inputs.push(t.clone());
+                } else {
+                    btor.latchs.push(t.clone());
+                }
+                if let [sym] = sym {
+                    btor.symbols.insert(t.clone(), sym.to_string());
+                }
+                t
+            }
+            ("init" | "next", [s, state, value]) => {
+                let sort = self.sort(s)?;
+                let state = self.node(state)?;
+                let v = self.node(value)?;
+                if !btor.latchs.contains(&state) || state.sort() != sort {
+                    return Err(self.syntax(&tokens));
+                }
+                if sort.is_array() && !v.sort().is_array() {
+                    return Err(self.error(Btor2ErrorKind::Unsupported(
+                        "constant array initialization".to_string(),
+                    )));
+                }
+                if v.sort() != sort {
+                    return Err(self.error(Btor2ErrorKind::InvalidSort(value.to_string())));
+                }
+                let map = if kind == "init" {
+                    &mut btor.init
+                } else {
+                    &mut btor.next
+                };
+                if map.insert(state, v).is_some() {
+                    return Err(self.syntax(&tokens));
+                }
+                return Ok(());
+            }
+            ("bad" | "constraint" | "fair" | "output", [t, ..]) => {
+                let t = if kind == "output" {
+                    self.node(t)?
+                } else {
+                    self.bool_node(t)?
+                };
+                match kind {
+                    "bad" => btor.bads.push(t),
+                    "constraint" => btor.constraints.push(t),
+                    "fair" => btor.fairness.push(t),
+                    _ => btor.outputs.push(t),
+                }
+                return Ok(());
+            }
+            ("justice", [n, ts @ ..]) => {
+                let n = self.num(n)?;
+                if ts.len() < n {
+                    return Err(self.syntax(&tokens));
+                }
+                let ts = ts[..n]
+                    .iter()
+                    .map(|t| self.bool_node(t))
+                    .collect::<Result<_, _>>()?;
+                btor.justice.push(ts);
+                return Ok(());
+            }
+            ("zero" | "one" | "ones", [s, ..]) => self.const_node(kind, self.sort(s)?, None)?,
+            ("const" | "constd" | "consth", [s, c, ..]) => {
+                self.const_node(kind, self.sort(s)?, Some(c))?
+            }
+            ("sext" | "uext", [s, a, w, ..]) => {
+                let a = self.bv_node(a)?;
+                let w = Term::bv_const_zero(self.num(w)?);
+                let t = if kind == "sext" {
+                    a.op1(Sext, &w)
+                } else {
+                    a.op1("uext", &w)
+                };
+                self.check_sort(t, self.sort(s)?)?
+            }
+            ("slice", [s, a, u, l, ..]) => {
+                let a = self.bv_node(a)?;
+                let (u, l) = (self.num(u)?, self.num(l)?);
+                if l > u || u >= a.bv_len() {
+                    return Err(self.syntax(&tokens));
+                }
+                self.check_sort(a.slice(l, u), self.sort(s)?)?
+            }
+            (_, [s, rest @ ..]) => {
+                let sort = self.sort(s)?;
+                let op = DynOp::get(kind)
+                    .ok_or_else(|| self.error(Btor2ErrorKind::UnknownOp(kind.to_string())))?;
+                if rest.len() < op.num_operand() {
+                    return Err(self.syntax(&tokens));
+                }
+                let rest = &rest[..op.num_operand()];
+                let terms: Vec<Term> = rest
+                    .iter()
+                    .map(|t| self.node(t))
+                    .collect::<Result<_, _>>()?;
+                if !operand_sort_valid(&op, &terms) {
+                    return Err(self.error(Btor2ErrorKind::InvalidSort(rest.join(" "))));
+                }
+                self.check_sort(Term::new_op(op, &terms), sort)?
+            }
+            _ => return Err(self.syntax(&tokens)),
+        };
+        self.nodes.insert(nid, term);
+        Ok(())
+    }
+
+    fn check_sort(&self, t: Term, sort: Sort) -> Result<Term, Btor2Error> {
+        if t.sort() != sort {
+            return Err(self.error(Btor2ErrorKind::InvalidSort(format!("{sort:?}"))));
+        }
+        Ok(t)
+    }
+}
+
+/// Whether `terms` have the operand sorts BTOR2 requires for `op`.
+fn operand_sort_valid(op: &DynOp, terms: &[Term]) -> bool {
+    let sorts: Vec<Sort> = terms.iter().map(|t| t.sort()).collect();
+    let same = sorts.iter().all(|s| *s == sorts[0]);
+    if *op == Read {
+        matches!(sorts[..], [Sort::Array(i, _), Sort::Bv(j)] if i == j)
+    } else if *op == WriteOp {
+        matches!(sorts[..], [Sort::Array(i, e), Sort::Bv(j), Sort::Bv(f)] if i == j && e == f)
+    } else if *op == Ite {
+        sorts[0] == Sort::Bv(1) && sorts[1] == sorts[2]
+    } else if *op == Eq || *op == Neq {
+        same
+    } else if *op == Iff || *op == Implies {
+        sorts.iter().all(|s| *s == Sort::Bv(1))
+    } else if *op == Concat {
+        sorts.iter().all(|s| !s.is_array())
+    } else {
+        same && !sorts[0].is_array()
+    }
+}
+
+/// Reads a BTOR2 model, ops are looked up by name in the `DynOp` registry.
+pub fn read_btor2<R: BufRead>(mut reader: R) -> Result<Btor2, Btor2Error> {
+    let mut btor = Btor2::new();
+    let mut parser = Btor2Parser {
+        line: 0,
+        sorts: GHashMap::new(),
+        nodes: GHashMap::new(),
+    };
+    let mut buf = String::new();
+    loop {
+        buf.clear();
+        let n = reader.read_line(&mut buf).map_err(|e| Btor2Error {
+            line: parser.line + 1,
+            kind: Btor2ErrorKind::Io(e),
+        })?;
+        if n == 0 {
+            break;
+        }
+        parser.line += 1;
+        parser.parse_line(&buf, &mut btor)?;
+    }
+    Ok(btor)
+}
+
+pub fn read_btor2_str(str: &str) -> Result<Btor2, Btor2Error> {
+    read_btor2(str.as_bytes())
+}
+
+pub fn read_btor2_file<P: AsRef<Path>>(file: P) -> Result<Btor2, Btor2Error> {
+    let file = open_compressed(file).map_err(|e| Btor2Error {
+        line: 0,
+        kind: Btor2ErrorKind::Io(e),
+    })?;
+    read_btor2(file)
+}
+
+struct Btor2Writer<W: Write> {
+    out: W,
+    nid: usize,
+    sorts: GHashMap<Sort, usize>,
+    nodes: GHashMap<Term, usize>,
+}
+
+impl<W: Write> Btor2Writer<W> {
+    fn new_nid(&mut self) -> usize {
+        self.nid += 1;
+        self.nid
+    }
+
+    fn sort(&mut self, sort: Sort) -> io::Result<usize> {
+        if let Some(s) = self.sorts.get(&sort) {
+            return Ok(*s);
+        }
+        let line = match sort {
+            Sort::Bv(w) => format!("sort bitvec {w}"),
+            Sort::Array(i, e) => {
+                let i = self.sort(Sort::Bv(i))?;
+                let e = self.sort(Sort::Bv(e))?;
+                format!("sort array {i} {e}")
+            }
+        };
+        let nid = self.new_nid();
+        writeln!(self.out, "{nid} {line}")?;
+        self.sorts.insert(sort, nid);
+        Ok(nid)
+    }
+
+    fn var(&mut self, t: &Term, kind: &str, sym: Option<&String>) -> io::Result<usize> {
+        let sid = self.sort(t.sort())?;
+        let nid = self.new_nid();
+        write!(self.out, "{nid} {kind} {sid}")?;
+        match sym {
+            Some(sym) => writeln!(self.out, " {sym}")?,
+            None => writeln!(self.out)?,
+        }
+        self.nodes.insert(t.clone(), nid);
+        Ok(nid)
+    }
+
+    fn term(&mut self, t: &Term) -> io::Result<usize> {
+        if let Some(n) = self.nodes.get(t) {
+            return Ok(*n);
+        }
+        let line = match t.deref() {
+            TermType::Const(c) => {
+                format!("const {} {c:b}", self.sort(t.sort())?)
+            }
+            TermType::Var(_) => return self.var(t, "input", None),
+            TermType::Op(o) => {
+                let name = o.op.name().to_lowercase();
+                let args: Vec<usize> = if o.op == Sext || o.op == Slice {
+                    vec![self.term(&o.terms[0])?]
+                } else {
+                    o.terms
+                        .iter()
+                        .map(|t| self.term(t))
+                        .collect::<io::Result<_>>()?
+                };
+                let mut line = format!("{name} {}", self.sort(t.sort())?);
+                for a in args {
+                    line.push_str(&format!(" {a}"));
+                }
+                for p in o
+                    .terms
+                    .iter()
+                    .skip(1)
+                    .filter(|_| o.op == Sext || o.op == Slice)
+                {
+                    line.push_str(&format!(" {}", p.bv_len()));
+                }
+                line
+            }
+        };
+        let nid = self.new_nid();
+        writeln!(self.out, "{nid} {line}")?;
+        self.nodes.insert(t.clone(), nid);
+        Ok(nid)
+    }
+
+    fn property(&mut self, kind: &str, t: &Term) -> io::Result<()> {
+        let t = self.term(t)?;
+        let nid = self.new_nid();
+        writeln!(self.out, "{nid} {kind} {t}")
+    }
+}
+
+/// Writes `btor` as BTOR2. Variables that are neither inputs nor states are
+/// declared as inputs.
+pub fn write_btor2<W: Write>(btor: &Btor2, out: W) -> io::Result<()> {
+    let mut w = Btor2Writer {
+        out,
+        nid: 0,
+        sorts: GHashMap::new(),
+        nodes: GHashMap::new(),
+    };
+    for i in btor.inputs.iter() {
+        w.var(i, "input", btor.symbols.get(i))?;
+    }
+    for l in btor.latchs.iter() {
+        w.var(l, "state", btor.symbols.get(l))?;
+    }
+    for (kind, map) in [("init", &btor.init), ("next", &btor.next)] {
+        for l in btor.latchs.iter() {
+            if let Some(v) = map.get(l) {
+                let v = w.term(v)?;
+                let (s, l) = (w.sort(l.sort())?, w.nodes[l]);
+                let nid = w.new_nid();
+                writeln!(w.out, "{nid} {kind} {s} {l} {v}")?;
+            }
+        }
+    }
+    for (kind, ts) in [
+        ("output", &btor.outputs),
+        ("bad", &btor.bads),
+        ("constraint", &btor.constraints),
+        ("fair", &btor.fairness),
+    ] {
+        for t in ts.iter() {
+            w.property(kind, t)?;
+        }
+    }
+    for j in btor.justice.iter() {
+        let ts: Vec<usize> = j.iter().map(|t| w.term(t)).collect::<io::Result<_>>()?;
+        let nid = w.new_nid();
+        write!(w.out, "{nid} justice {}", ts.len())?;
+        for t in ts {
+            write!(w.out, " {t}")?;
+        }
+        writeln!(w.out)?;
+    }
+    Ok(())
+}
+
+pub fn write_btor2_file<P: AsRef<Path>>(btor: &Btor2, file: P) -> io::Result<()> {
+    let mut file = create_compressed(file)?;
+    write_btor2(btor, &mut file)?;
+    file.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fol::TEST_LOCK;
+
+    const BTOR2: &str = "; counter with memory
+1 sort bitvec 1
+2 sort bitvec 4
+3 sort array 2 2
+4 input 2 in
+5 state 2 cnt
+6 state 3 mem
+7 zero 2
+8 init 2 5 7
+9 one 2
+10 add 2 5 9
+11 write 3 6 5 4
+12 read 2 6 -4
+13 ult 1 12 10
+14 next 2 5 10
+15 next 3 6 11
+16 constd 2 -1
+17 eq 1 5 16
+18 bad 17
+19 slice 1 5 3 3
+20 uext 2 19 3
+21 sext 2 19 3
+22 neq 1 20 21
+23 constraint -22
+24 ite 2 13 5 12
+25 consth 2 a
+26 justice 2 13 -17
+27 output 24
+28 fair -13
+";
+
+    fn check_terms(btor: &Btor2) {
+        let (input, cnt, mem) = (&btor.inputs[0], &btor.latchs[0], &btor.latchs[1]);
+        let inc = cnt + Term::bv_const_one(4);
+        assert_eq!(btor.next[cnt], inc);
+        assert_eq!(btor.next[mem], mem.op2(WriteOp, cnt, input));
+        let read = mem.op1(Read, &!input);
+        let lt = read.op1("ult", &inc);
+        assert_eq!(btor.outputs, vec![lt.ite(cnt, &read)]);
+        assert_eq!(btor.fairness, vec![!&lt]);
+        let top = cnt.slice(3, 3);
+        let ext = Term::bv_const_zero(3);
+        let neq = top.op1("uext", &ext).op1("neq", &top.op1(Sext, &ext));
+        assert_eq!(btor.constraints, vec![!neq]);
+        let ones = Term::bv_const_ones(4);
+        assert_eq!(btor.bads, vec![cnt.op1("eq", &ones)]);
+        assert_eq!(btor.justice, vec![vec![lt, !&btor.bads[0]]]);
+    }
+
+    #[test]
+    fn test_btor2() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let btor = read_btor2_str(BTOR2).unwrap();
+        assert_eq!(btor.inputs.len(), 1);
+        assert_eq!(btor.latchs[1].sort(), Sort::Array(4, 4));
+        assert_eq!(btor.init[&btor.latchs[0]], Term::bv_const_zero(4));
+        assert_eq!(btor.symbols[&btor.latchs[0]], "cnt");
+        check_terms(&btor);
+        let mut out = Vec::new();
+        write_btor2(&btor, &mut out).unwrap();
+        let back = read_btor2(out.as_slice()).unwrap();
+        check_terms(&back);
+        let mut out2 = Vec::new();
+        write_btor2(&back, &mut out2).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(out2).unwrap()
+        );
+        for bad in [
+            "1 sort bitvec 4\n2 foo 1 1\n",
+            "1 sort bitvec 4\n2 constd 1 16\n",
+            "1 sort bitvec 4\n2 not 1 3\n",
+            "1 sort bitvec 4\n2 sort bitvec 1\n3 input 1\n4 ult 1 3 3\n",
+        ] {
+            assert!(read_btor2_str(bad).is_err());
+        }
+        for bad in [
+            "1 sort bitvec 4\n2 sort bitvec 2\n3 input 1\n4 input 2\n5 add 1 3 4\n",
+            "1 sort bitvec 4\n2 sort bitvec 2\n3 state 1\n4 input 2\n5 init 1 3 4\n",
+            "1 sort bitvec 4\n2 sort bitvec 2\n3 sort array 1 1\n4 state 3\n5 input 2\n6 read 1 4 5\n",
+            "1 sort bitvec 4\n2 sort bitvec 1\n3 input 1\n4 ite 1 3 3 3\n",
+            "1 sort bitvec 4\n2 input 1\n3 bad 2\n",
+            "1 sort bitvec 4\n2 input 1\n3 constraint -2\n",
+            "1 sort bitvec 4\n2 input 1\n3 fair 2\n",
+            "1 sort bitvec 4\n2 sort bitvec 1\n3 input 2\n4 input 1\n5 justice 2 3 4\n",
+        ] {
+            let err = read_btor2_str(bad).unwrap_err();
+            assert!(matches!(err.kind, Btor2ErrorKind::InvalidSort(_)), "{bad}");
+        }
+        let err = "1 sort bitvec 2\n2 sort array 1 1\n3 state 2\n4 zero 1\n5 init 2 3 4\n";
+        assert!(matches!(
+            read_btor2_str(err).unwrap_err().kind,
+            Btor2ErrorKind::Unsupported(_)
+        ));
+        let err = read_btor2_str("1 sort bitvec 4\n2 input 1\n3 not 1 -9223372036854775808\n");
+        assert!(matches!(
+            err.unwrap_err().kind,
+            Btor2ErrorKind::UndefinedNode(i64::MIN)
+        ));
+    }
+}
@@ -0,0 +1,529 @@
+use std::fmt::{self, Debug, Display};
+use std::iter::repeat_n;
+use std::ops::{self, Index};
+
+/// A bitvector constant of arbitrary width, packed LSB first into 64-bit words.
+/// Bits above `len` in the last word are always zero.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BvConst {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BvConst {
+    #[inline]
+    pub fn new(c: &[bool]) -> Self {
+        c.iter().copied().collect()
+    }
+
+    #[inline]
+    pub fn zero(len: usize) -> Self {
+        Self {
+            len,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    #[inline]
+    pub fn one(len: usize) -> Self {
+        Self::from_u64(1, len)
+    }
+
+    #[inline]
+    pub fn ones(len: usize) -> Self {
+        !&Self::zero(len)
+    }
+
+    /// The low `len` bits of `v`.
+    #[inline]
+    pub fn from_u64(v: u64, len: usize) -> Self {
+        Self::from_u128(v as u128, len)
+    }
+
+    /// The low `len` bits of `v`.
+    pub fn from_u128(v: u128, len: usize) -> Self {
+        let mut res = Self::zero(len);
+        for (i, w) in res.words.iter_mut().take(2).enumerate() {
+            *w = (v >> (64 * i)) as u64;
+        }
+        res.mask();
+        res
+    }
+
+    /// Parses the digits `s` in `radix` 2, 10 or 16, with an optional leading
+    /// `-` for two's complement negation. Fails if the magnitude needs more
+    /// than `len` bits.
+    pub fn from_str_radix(s: &str, radix: u32, len: usize) -> Option<Self> {
+        if !matches!(radix, 2 | 10 | 16) {
+            return None;
+        }
+        let (neg, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        if s.is_empty() {
+            return None;
+        }
+        let mut words: Vec<u64> = Vec::new();
+        for c in s.chars() {
+            let mut carry = c.to_digit(radix)? as u128;
+            for w in words.iter_mut() {
+                let t = *w as u128 * radix as u128 + carry;
+                *w = t as u64;
+                carry = t >> 64;
+            }
+            if carry != 0 {
+                words.push(carry as u64);
+            }
+        }
+        let bits = words
+            .last()
+            .map_or(0, |w| 64 * words.len() - w.leading_zeros() as usize);
+        if bits > len {
+            return None;
+        }
+        words.resize(len.div_ceil(64), 0);
+        let res = Self { len, words };
+        Some(if neg { -&res } else { res })
+    }
+
+    /// The value as `u64`, if it fits.
+    #[inline]
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_u128().and_then(|v| u64::try_from(v).ok())
+    }
+
+    /// The value as `u128`, if it fits.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.words.iter().skip(2).any(|w| *w != 0) {
+            return None;
+        }
+        let lo = self.words.first().copied().unwrap_or(0) as u128;
+        let hi = self.words.get(1).copied().unwrap_or(0) as u128;
+        Some(lo | (hi << 64))
+    }
+
+    #[inline]
+    fn mask(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            *self.words.last_mut().unwrap() &= (1 << (self.len % 64)) - 1;
+        }
+    }
+
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize) -> bool {
+        debug_assert!(i < self.len);
+        (self.words[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    #[inline]
+    pub fn set(&mut self, i: usize, v: bool) {
+        debug_assert!(i < self.len);
+        let w = &mut self.words[i / 64];
+        *w = (*w & !(1 << (i % 64))) | ((v as u64) << (i % 64));
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    /// The bits, least significant first.
+    #[inline]
+    pub fn to_bits(&self) -> Vec<bool> {
+        self.iter().collect()
+    }
+
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    #[inline]
+    pub fn is_ones(&self) -> bool {
+        *self == Self::ones(self.len)
+    }
+
+    pub fn bool(&self) -> Option<bool> {
+        if self.len == 1 {
+            Some(self.get(0))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn sign(&self) -> bool {
+        self.get(self.len - 1)
+    }
+
+    #[inline]
+    fn zip_words(&self, rhs: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+        debug_assert!(self.len == rhs.len);
+        let words = self
+            .words
+            .iter()
+            .zip(rhs.words.iter())
+            .map(|(x, y)| f(*x, *y))
+            .collect();
+        Self {
+            len: self.len,
+            words,
+        }
+    }
+
+    fn add_carry(&self, rhs: &Self, mut carry: bool) -> (Self, bool) {
+        debug_assert!(self.len == rhs.len);
+        let mut res = Self::zero(self.len);
+        for (r, (x, y)) in res.words.iter_mut().zip(self.words.iter().zip(&rhs.words)) {
+            let (s, c0) = x.overflowing_add(*y);
+            let (s, c1) = s.overflowing_add(carry as u64);
+            *r = s;
+            carry = c0 || c1;
+        }
+        if !self.len.is_multiple_of(64) {
+            carry = (res.words.last().unwrap() >> (self.len % 64)) & 1 == 1;
+            res.mask();
+        }
+        (res, carry)
+    }
+
+    /// The wrapped sum and whether the unsigned addition overflowed.
+    #[inline]
+    pub fn overflowing_add(&self, rhs: &Self) -> (Self, bool) {
+        self.add_carry(rhs, false)
+    }
+
+    /// The wrapped difference and whether the unsigned subtraction borrowed.
+    #[inline]
+    pub fn overflowing_sub(&self, rhs: &Self) -> (Self, bool) {
+        let (r, c) = self.add_carry(&!rhs, true);
+        (r, !c)
+    }
+
+    fn wrapping_mul(&self, rhs: &Self) -> Self {
+        debug_assert!(self.len == rhs.len);
+        let n = self.words.len();
+        let mut res = Self::zero(self.len);
+        for (i, x) in self.words.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, y) in rhs.words.iter().take(n - i).enumerate() {
+                let t = *x as u128 * *y as u128 + res.words[i + j] as u128 + carry;
+                res.words[i + j] = t as u64;
+                carry = t >> 64;
+            }
+        }
+        res.mask();
+        res
+    }
+
+    /// Unsigned quotient and remainder, with SMT-LIB semantics for a zero
+    /// divisor: all ones and the dividend.
+    pub fn udiv_urem(&self, rhs: &Self) -> (Self, Self) {
+        debug_assert!(self.len == rhs.len);
+        if rhs.is_zero() {
+            return (Self::ones(self.len), self.clone());
+        }
+        let mut q = Self::zero(self.len);
+        let mut r = Self::zero(self.len);
+        for i in (0..self.len).rev() {
+            let carry = r.sign();
+            r = r.shl(1);
+            r.set(0, self.get(i));
+            if carry || !r.ult(rhs) {
+                r = &r - rhs;
+                q.set(i, true);
+            }
+        }
+        (q, r)
+    }
+
+    #[inline]
+    pub fn udiv(&self, rhs: &Self) -> Self {
+        self.udiv_urem(rhs).0
+    }
+
+    #[inline]
+    pub fn urem(&self, rhs: &Self) -> Self {
+        self.udiv_urem(rhs).1
+    }
+
+    pub fn shl(&self, n: usize) -> Self {
+        let mut res = Self::zero(self.len);
+        if n >= self.len {
+            return res;
+        }
+        let (ws, bs) = (n / 64, n % 64);
+        for i in ws..res.words.len() {
+            let mut w = self.words[i - ws] << bs;
+            if bs > 0 && i > ws {
+                w |= self.words[i - ws - 1] >> (64 - bs);
+            }
+            res.words[i] = w;
+        }
+        res.mask();
+        res
+    }
+
+    pub fn lshr(&self, n: usize) -> Self {
+        let mut res = Self::zero(self.len);
+        if n >= self.len {
+            return res;
+        }
+        let (ws, bs) = (n / 64, n % 64);
+        let nw = self.words.len();
+        for i in 0..nw - ws {
+            let mut w = self.words[i + ws] >> bs;
+            if bs > 0 && i + ws + 1 < nw {
+                w |= self.words[i + ws + 1] << (64 - bs);
+            }
+            res.words[i] = w;
+        }
+        res
+    }
+
+    pub fn ashr(&self, n: usize) -> Self {
+        let res = self.lshr(n);
+        if self.len > 0 && self.sign() {
+            &res | &!&Self::ones(self.len).lshr(n)
+        } else {
+            res
+        }
+    }
+
+    #[inline]
+    pub fn rotl(&self, n: usize) -> Self {
+        let n = n % self.len;
+        if n == 0 {
+            return self.clone();
+        }
+        &self.shl(n) | &self.lshr(self.len - n)
+    }
+
+    #[inline]
+    pub fn rotr(&self, n: usize) -> Self {
+        self.rotl(self.len - n % self.len)
+    }
+
+    pub fn ult(&self, rhs: &Self) -> bool {
+        debug_assert!(self.len == rhs.len);
+        for (x, y) in self.words.iter().zip(rhs.words.iter()).rev() {
+            if x != y {
+                return x < y;
+            }
+        }
+        false
+    }
+
+    #[inline]
+    pub fn slt(&self, rhs: &Self) -> bool {
+        match (self.sign(), rhs.sign()) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => self.ult(rhs),
+        }
+    }
+
+    /// `self` as the high part above `low`.
+    #[inline]
+    pub fn concat(&self, low: &Self) -> Self {
+        low.iter().chain(self.iter()).collect()
+    }
+
+    /// Bits `l` to `h` inclusive.
+    #[inline]
+    pub fn extract(&self, l: usize, h: usize) -> Self {
+        (l..=h).map(|i| self.get(i)).collect()
+    }
+
+    #[inline]
+    pub fn uext(&self, n: usize) -> Self {
+        self.iter().chain(repeat_n(false, n)).collect()
+    }
+
+    #[inline]
+    pub fn sext(&self, n: usize) -> Self {
+        self.iter().chain(repeat_n(self.sign(), n)).collect()
+    }
+}
+
+impl FromIterator<bool> for BvConst {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let mut res = Self::zero(0);
+        for b in iter {
+            if res.len.is_multiple_of(64) {
+                res.words.push(0);
+            }
+            res.len += 1;
+            res.set(res.len - 1, b);
+        }
+        res
+    }
+}
+
+impl Index<usize> for BvConst {
+    type Output = bool;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        if self.get(index) { &true } else { &false }
+    }
+}
+
+impl Debug for BvConst {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BvConst({self:b})")
+    }
+}
+
+impl fmt::Binary for BvConst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s: String = self
+            .iter()
+            .rev()
+            .map(|b| if b { '1' } else { '0' })
+            .collect();
+        f.write_str(&s)
+    }
+}
+
+impl fmt::LowerHex for BvConst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s: String = (0..self.len.div_ceil(4))
+            .rev()
+            .map(|d| {
+                let v = (4 * d..(4 * d + 4).min(self.len))
+                    .fold(0, |v, i| v | (self.get(i) as u32) << (i - 4 * d));
+                char::from_digit(v, 16).unwrap()
+            })
+            .collect();
+        f.write_str(&s)
+    }
+}
+
+/// SMT-LIB form: `#x` when the width is a multiple of 4, `#b` otherwise.
+impl Display for BvConst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.len > 0 && self.len.is_multiple_of(4) {
+            write!(f, "#x{self:x}")
+        } else {
+            write!(f, "#b{self:b}")
+        }
+    }
+}
+
+impl ops::Not for &BvConst {
+    type Output = BvConst;
+
+    #[inline]
+    fn not(self) -> Self::Output {
+        let mut res = BvConst {
+            len: self.len,
+            words: self.words.iter().map(|w| !w).collect(),
+        };
+        res.mask();
+        res
+    }
+}
+
+impl ops::Neg for &BvConst {
+    type Output = BvConst;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        (!self).add_carry(&BvConst::zero(self.len), true).0
+    }
+}
+
+macro_rules! impl_bv_const_biops {
+    ($trait:ident, $method:ident, $f:expr) => {
+        impl ops::$trait for &BvConst {
+            type Output = BvConst;
+
+            #[inline]
+            fn $method(self, rhs: Self) -> Self::Output {
+                $f(self, rhs)
+            }
+        }
+    };
+}
+
+impl_bv_const_biops!(BitAnd, bitand, |x: &BvConst, y| x
+    .zip_words(y, |x, y| x & y));
+impl_bv_const_biops!(BitOr, bitor, |x: &BvConst, y| x.zip_words(y, |x, y| x | y));
+impl_bv_const_biops!(BitXor, bitxor, |x: &BvConst, y| x
+    .zip_words(y, |x, y| x ^ y));
+impl_bv_const_biops!(Add, add, |x: &BvConst, y| x.overflowing_add(y).0);
+impl_bv_const_biops!(Sub, sub, |x: &BvConst, y| x.overflowing_sub(y).0);
+impl_bv_const_biops!(Mul, mul, BvConst::wrapping_mul);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn test_bv_const() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for len in [1, 7, 63, 64, 65, 100, 128] {
+            let m = if len == 128 {
+                u128::MAX
+            } else {
+                (1 << len) - 1
+            };
+            for _ in 0..200 {
+                let (a, b) = (rng.random::<u128>() & m, rng.random::<u128>() & m);
+                let (x, y) = (BvConst::from_u128(a, len), BvConst::from_u128(b, len));
+                let n = rng.random_range(0..len + 2);
+                assert_eq!((&x + &y).to_u128(), Some(a.wrapping_add(b) & m));
+                assert_eq!((&x - &y).to_u128(), Some(a.wrapping_sub(b) & m));
+                assert_eq!((&x * &y).to_u128(), Some(a.wrapping_mul(b) & m));
+                assert_eq!((-&x).to_u128(), Some(a.wrapping_neg() & m));
+                assert_eq!(x.udiv(&y).to_u128(), a.checked_div(b).or(Some(m)));
+                assert_eq!(x.urem(&y).to_u128(), a.checked_rem(b).or(Some(a)));
+                assert_eq!(
+                    x.shl(n).to_u128(),
+                    Some(a.checked_shl(n as u32).unwrap_or(0) & m)
+                );
+                assert_eq!(
+                    x.lshr(n).to_u128(),
+                    Some(a.checked_shr(n as u32).unwrap_or(0))
+                );
+                assert_eq!(x.ult(&y), a < b);
+                assert_eq!(
+                    x.overflowing_add(&y).1,
+                    a.checked_add(b).is_none_or(|s| s > m)
+                );
+                assert_eq!(x.overflowing_sub(&y).1, a < b);
+                assert_eq!(x.rotl(n).rotr(n), x);
+                assert_eq!(x.concat(&y).extract(len, 2 * len - 1), x);
+                assert_eq!(x.sext(3).extract(0, len - 1), x);
+                let hex = format!("{:x}", x.uext((4 - len % 4) % 4));
+                assert_eq!(BvConst::from_str_radix(&hex, 16, len), Some(x.clone()));
+                let dec = a.to_string();
+                assert_eq!(BvConst::from_str_radix(&dec, 10, len), Some(x.clone()));
+            }
+        }
+        let x = BvConst::from_u64(0b1010, 4);
+        assert_eq!(x.to_bits(), [false, true, false, true]);
+        assert_eq!(BvConst::new(&x.to_bits()), x);
+        assert_eq!(x.to_string(), "#xa");
+        assert_eq!(x.ashr(2).to_string(), "#xe");
+        assert_eq!(x.extract(0, 2).to_string(), "#b010");
+        assert!(x.slt(&BvConst::zero(4)));
+        assert_eq!(x.udiv(&BvConst::zero(4)), BvConst::ones(4));
+        assert_eq!(
+            BvConst::from_str_radix("-3", 10, 4),
+            Some(BvConst::from_u64(13, 4))
+        );
+        assert_eq!(BvConst::from_str_radix("10000", 2, 4), None);
+        assert_eq!(BvConst::from_str_radix("1x", 16, 8), None);
+    }
+}
@@ -0,0 +1,226 @@
+use super::{BvConst, Sort, Term, TermType};
+use giputils::hash::{GHashMap, GHashSet};
+use std::ops::Deref;
+
+/// An array value: `default` at every index except those in `entries`.
+#[derive(Clone, Debug)]
+pub struct ArrayValue {
+    index_len: usize,
+    default: BvConst,
+    entries: GHashMap<BvConst, BvConst>,
+}
+
+impl ArrayValue {
+    #[inline]
+    pub fn new(index_len: usize, default: BvConst) -> Self {
+        Self {
+            index_len,
+            default,
+            entries: GHashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn sort(&self) -> Sort {
+        Sort::Array(self.index_len, self.default.len())
+    }
+
+    #[inline]
+    pub fn read(&self, index: &BvConst) -> BvConst {
+        debug_assert!(index.len() == self.index_len);
+        self.entries.get(index).unwrap_or(&self.default).clone()
+    }
+
+    #[inline]
+    pub fn write(&mut self, index: BvConst, value: BvConst) {
+        debug_assert!(index.len() == self.index_len && value.len() == self.default.len());
+        if value == self.default {
+            self.entries.remove(&index);
+        } else {
+            self.entries.insert(index, value);
+        }
+    }
+}
+
+impl PartialEq for ArrayValue {
+    fn eq(&self, other: &Self) -> bool {
+        if self.sort() != other.sort() {
+            return false;
+        }
+        let keys = self.entries.keys().chain(other.entries.keys());
+        if keys.clone().any(|k| self.read(k) != other.read(k)) {
+            return false;
+        }
+        if self.default == other.default {
+            return true;
+        }
+        // Differing defaults only matter if some index is left uncovered.
+        let covered: GHashSet<&BvConst> = keys.collect();
+        self.index_len < usize::BITS as usize && covered.len() == 1 << self.index_len
+    }
+}
+
+impl Eq for ArrayValue {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Bv(BvConst),
+    Array(ArrayValue),
+}
+
+impl Value {
+    #[inline]
+    pub fn sort(&self) -> Sort {
+        match self {
+            Value::Bv(c) => Sort::Bv(c.len()),
+            Value::Array(a) => a.sort(),
+        }
+    }
+
+    #[inline]
+    pub fn bv(&self) -> &BvConst {
+        match self {
+            Value::Bv(c) => c,
+            Value::Array(_) => panic!("{self:?} is not a bitvector"),
+        }
+    }
+
+    #[inline]
+    pub fn array(&self) -> &ArrayValue {
+        match self {
+            Value::Array(a) => a,
+            Value::Bv(_) => panic!("{self:?} is not an array"),
+        }
+    }
+}
+
+impl From<BvConst> for Value {
+    #[inline]
+    fn from(c: BvConst) -> Self {
+        Value::Bv(c)
+    }
+}
+
+impl From<ArrayValue> for Value {
+    #[inline]
+    fn from(a: ArrayValue) -> Self {
+        Value::Array(a)
+    }
+}
+
+impl Term {
+    /// Evaluates the term with its leaves taken from `env`, which must assign
+    /// every variable the term depends on.
+    pub fn eval(&self, env: &GHashMap<Term, Value>) -> Value {
+        let mut cache = GHashMap::new();
+        self.eval_rec(env, &mut cache)
+    }
+
+    fn eval_rec(&self, env: &GHashMap<Term, Value>, cache: &mut GHashMap<Term, Value>) -> Value {
+        if let Some(v) = env.get(self).or_else(|| cache.get(self)) {
+            return v.clone();
+        }
+        let value = match self.deref() {
+            TermType::Const(c) => Value::Bv(c.clone()),
+            TermType::Var(_) => panic!("{self:?} is unassigned"),
+            TermType::Op(op_term) => {
+                let terms: Vec<Value> = op_term
+                    .terms
+                    .iter()
+                    .map(|s| s.eval_rec(env, cache))
+                    .collect();
+                op_term
+                    .op
+                    .eval(&terms)
+                    .unwrap_or_else(|| panic!("{:?} not support eval", op_term.op))
+            }
+        };
+        debug_assert!(value.sort() == self.sort());
+        cache.insert(self.clone(), value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        DagCnf, Lit,
+        fol::{
+            TEST_LOCK,
+            op::{Concat, Mul, Read, Rol, Ror, Sext, Sll, Slt, Sra, Srl, Udiv, Ult, Urem, Write},
+        },
+        satif::{CdclSolver, Satif},
+    };
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn random_bv(rng: &mut StdRng, len: usize) -> BvConst {
+        (0..len).map(|_| rng.random()).collect()
+    }
+
+    #[test]
+    fn test_eval() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let x = Term::new_var(Sort::Bv(5));
+        let y = Term::new_var(Sort::Bv(5));
+        let m = Term::new_var(Sort::Array(2, 5));
+        let i = x.slice(1, 2);
+        let mw = m.op2(Write, &i, &(&x + &y));
+        let r = mw.op1(Read, &y.slice(0, 1));
+        let terms = [
+            x.op1(Mul, &y),
+            x.op1(Udiv, &y),
+            x.op1(Urem, &y),
+            x.op1(Sll, &y),
+            x.op1(Srl, &y),
+            x.op1(Sra, &y),
+            x.op1(Rol, &y),
+            x.op1(Ror, &y),
+            (x.op1(Ult, &y) ^ x.op1(Slt, &y)).ite(&(&x & &y), &(&x | !&y)),
+            x.op1(Concat, &y).slice(3, 7),
+            x.slice(0, 2).op1(Sext, &Term::bv_const_zero(2)),
+            mw.op1(crate::fol::op::Eq, &m).op1(Concat, &r),
+        ];
+        let mut bb = GHashMap::new();
+        let mut cm = GHashMap::new();
+        let mut dc = DagCnf::new();
+        let mut enc = |t: &Term| -> Vec<Lit> {
+            t.bitblast(&mut bb)
+                .iter()
+                .map(|b| b.cnf_encode(&mut dc, &mut cm))
+                .collect()
+        };
+        let (xl, yl, ml) = (enc(&x), enc(&y), enc(&m));
+        let tl: Vec<Vec<Lit>> = terms.iter().map(&mut enc).collect();
+        let mut solver = CdclSolver::new();
+        for c in dc.clause() {
+            solver.add_clause(c);
+        }
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..64 {
+            let (xv, yv) = (random_bv(&mut rng, 5), random_bv(&mut rng, 5));
+            let mut mv = ArrayValue::new(2, random_bv(&mut rng, 5));
+            mv.write(random_bv(&mut rng, 2), random_bv(&mut rng, 5));
+            let mut assumps = Vec::new();
+            let mut fix = |l: &[Lit], v: &BvConst| {
+                assumps.extend(l.iter().zip(v.iter()).map(|(l, v)| l.not_if(!v)));
+            };
+            fix(&xl, &xv);
+            fix(&yl, &yv);
+            for j in 0..4 {
+                let idx = BvConst::new(&[j & 1 == 1, j & 2 == 2]);
+                fix(&ml[5 * j..5 * (j + 1)], &mv.read(&idx));
+            }
+            assert!(solver.solve(&assumps));
+            let env = GHashMap::from_iter([
+                (x.clone(), Value::from(xv)),
+                (y.clone(), Value::from(yv)),
+                (m.clone(), Value::from(mv)),
+            ]);
+            for (t, l) in terms.iter().zip(tl.iter()) {
+                let model: Vec<bool> = l.iter().map(|l| solver.sat_value(*l).unwrap()).collect();
+                assert_eq!(t.eval(&env), Value::Bv(BvConst::new(&model)), "{t:?}");
+            }
+        }
+    }
+}
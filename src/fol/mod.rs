@@ -1,11 +1,24 @@
 pub mod bitblast;
+pub mod btor2;
+mod bvconst;
+mod eval;
 pub mod op;
 mod replace;
+mod rewrite;
 mod simplify;
+pub mod smtlib2;
 mod sort;
 mod term;
 mod utils;
 
+pub use bvconst::*;
+pub use eval::*;
+pub use rewrite::*;
 pub use sort::*;
 pub use term::*;
 pub use utils::*;
+
+/// Serializes tests that build terms, the term manager is a global without
+/// synchronization.
+#[cfg(test)]
+pub(crate) static TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
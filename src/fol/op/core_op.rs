@@ -1,6 +1,6 @@
 use super::define::define_core_op;
 use super::{Sort, Term, TermResult, TermVec};
-use crate::fol::BvConst;
+use crate::fol::{BvConst, Value};
 use crate::{DagCnf, Lit, LitVvec};
 
 #[inline]
@@ -8,7 +8,41 @@ fn bool_sort(_terms: &[Term]) -> Sort {
     Sort::Bv(1)
 }
 
-define_core_op!(Not, 1, bitblast: not_bitblast, cnf_encode: not_cnf_encode, simplify: not_simplify);
+#[inline]
+fn bool_value(b: bool) -> Value {
+    Value::Bv(BvConst::new(&[b]))
+}
+
+/// The shift amount held in `y`, saturating at `usize::MAX`.
+#[inline]
+fn shift_amount(y: &BvConst) -> usize {
+    y.to_u64()
+        .and_then(|v| usize::try_from(v).ok())
+        .unwrap_or(usize::MAX)
+}
+
+/// The rotation amount held in `y`, modulo the width.
+#[inline]
+fn rotate_amount(y: &BvConst) -> usize {
+    let w = BvConst::from_u64(y.len() as u64, y.len());
+    y.urem(&w).to_u64().unwrap() as usize
+}
+
+/// Shifting or rotating by zero is the identity.
+#[inline]
+fn shift_simplify(terms: &[Term]) -> TermResult {
+    if terms[1].try_bv_const().is_some_and(|c| c.is_zero()) {
+        return TermResult::Some(terms[0].clone());
+    }
+    TermResult::None
+}
+
+#[inline]
+fn signed_overflow_value(x: &BvConst, y: &BvConst, r: &BvConst, same: bool) -> bool {
+    ((x.sign() ^ y.sign()) != same) && (x.sign() ^ r.sign())
+}
+
+define_core_op!(Not, 1, bitblast: not_bitblast, cnf_encode: not_cnf_encode, simplify: not_simplify, eval: not_eval);
 fn not_simplify(terms: &[Term]) -> TermResult {
     let x = &terms[0];
     if let Some(op) = x.try_op()
@@ -27,8 +61,11 @@ fn not_bitblast(terms: &[TermVec]) -> TermVec {
 fn not_cnf_encode(_dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     !terms[0]
 }
+fn not_eval(terms: &[Value]) -> Value {
+    Value::Bv(!terms[0].bv())
+}
 
-define_core_op!(And, 2, bitblast: and_bitblast, cnf_encode: and_cnf_encode, simplify: and_simplify);
+define_core_op!(And, 2, bitblast: and_bitblast, cnf_encode: and_cnf_encode, simplify: and_simplify, eval: and_eval);
 fn and_simplify(terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
@@ -97,8 +134,11 @@ fn and_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     dc.add_rel(l.var(), &LitVvec::cnf_and(l, terms));
     l
 }
+fn and_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv() & terms[1].bv())
+}
 
-define_core_op!(Or, 2, bitblast: or_bitblast, cnf_encode: or_cnf_encode, simplify: or_simplify);
+define_core_op!(Or, 2, bitblast: or_bitblast, cnf_encode: or_cnf_encode, simplify: or_simplify, eval: or_eval);
 fn or_simplify(terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
@@ -165,8 +205,11 @@ fn or_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     dc.add_rel(l.var(), &LitVvec::cnf_or(l, terms));
     l
 }
+fn or_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv() | terms[1].bv())
+}
 
-define_core_op!(Xor, 2, bitblast: xor_bitblast, cnf_encode: xor_cnf_encode, simplify: xor_simplify);
+define_core_op!(Xor, 2, bitblast: xor_bitblast, cnf_encode: xor_cnf_encode, simplify: xor_simplify, eval: xor_eval);
 fn xor_simplify(terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
@@ -198,8 +241,11 @@ fn xor_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     dc.add_rel(l.var(), &LitVvec::cnf_xor(l, terms[0], terms[1]));
     l
 }
+fn xor_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv() ^ terms[1].bv())
+}
 
-define_core_op!(Eq, 2, sort: bool_sort, bitblast: eq_bitblast, cnf_encode: eq_cnf_encode, simplify: eq_simplify);
+define_core_op!(Eq, 2, sort: bool_sort, bitblast: eq_bitblast, cnf_encode: eq_cnf_encode, simplify: eq_simplify, eval: eq_eval);
 fn eq_simplify(terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
@@ -229,8 +275,11 @@ fn eq_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     dc.add_rel(l.var(), &LitVvec::cnf_xnor(l, terms[0], terms[1]));
     l
 }
+fn eq_eval(terms: &[Value]) -> Value {
+    bool_value(terms[0] == terms[1])
+}
 
-define_core_op!(Ult, 2, sort: bool_sort, bitblast: ult_bitblast, simplify: ult_simplify);
+define_core_op!(Ult, 2, sort: bool_sort, bitblast: ult_bitblast, simplify: ult_simplify, eval: ult_eval);
 fn ult_simplify(terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
@@ -259,8 +308,17 @@ fn ult_bitblast(terms: &[TermVec]) -> TermVec {
     }
     TermVec::from([res])
 }
+fn ult_eval(terms: &[Value]) -> Value {
+    bool_value(terms[0].bv().ult(terms[1].bv()))
+}
 
-define_core_op!(Slt, 2, sort: bool_sort, bitblast: slt_bitblast);
+define_core_op!(Slt, 2, sort: bool_sort, bitblast: slt_bitblast, simplify: slt_simplify, eval: slt_eval);
+fn slt_simplify(terms: &[Term]) -> TermResult {
+    if terms[0] == terms[1] {
+        return TermResult::Some(Term::bool_const(false));
+    }
+    TermResult::None
+}
 fn slt_bitblast(terms: &[TermVec]) -> TermVec {
     let x = &terms[0];
     let y = &terms[1];
@@ -275,8 +333,14 @@ fn slt_bitblast(terms: &[TermVec]) -> TermVec {
     }
     TermVec::from([ls | (eqs & el)])
 }
+fn slt_eval(terms: &[Value]) -> Value {
+    bool_value(terms[0].bv().slt(terms[1].bv()))
+}
 
-define_core_op!(Sll, 2, bitblast: sll_bitblast);
+define_core_op!(Sll, 2, bitblast: sll_bitblast, simplify: sll_simplify, eval: sll_eval);
+fn sll_simplify(terms: &[Term]) -> TermResult {
+    shift_simplify(terms)
+}
 fn sll_bitblast(terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert!(x.len() == y.len());
@@ -306,8 +370,14 @@ fn sll_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn sll_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().shl(shift_amount(terms[1].bv())))
+}
 
-define_core_op!(Srl, 2, bitblast: srl_bitblast);
+define_core_op!(Srl, 2, bitblast: srl_bitblast, simplify: srl_simplify, eval: srl_eval);
+fn srl_simplify(terms: &[Term]) -> TermResult {
+    shift_simplify(terms)
+}
 fn srl_bitblast(terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert!(x.len() == y.len());
@@ -338,8 +408,14 @@ fn srl_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn srl_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().lshr(shift_amount(terms[1].bv())))
+}
 
-define_core_op!(Sra, 2, bitblast: sra_bitblast);
+define_core_op!(Sra, 2, bitblast: sra_bitblast, simplify: sra_simplify, eval: sra_eval);
+fn sra_simplify(terms: &[Term]) -> TermResult {
+    shift_simplify(terms)
+}
 fn sra_bitblast(terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert!(x.len() == y.len());
@@ -371,8 +447,14 @@ fn sra_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn sra_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().ashr(shift_amount(terms[1].bv())))
+}
 
-define_core_op!(Rol, 2, bitblast: rol_bitblast);
+define_core_op!(Rol, 2, bitblast: rol_bitblast, simplify: rol_simplify, eval: rol_eval);
+fn rol_simplify(terms: &[Term]) -> TermResult {
+    shift_simplify(terms)
+}
 fn rol_bitblast(terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert_eq!(x.len(), y.len());
@@ -405,8 +487,14 @@ fn rol_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn rol_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().rotl(rotate_amount(terms[1].bv())))
+}
 
-define_core_op!(Ror, 2, bitblast: ror_bitblast);
+define_core_op!(Ror, 2, bitblast: ror_bitblast, simplify: ror_simplify, eval: ror_eval);
+fn ror_simplify(terms: &[Term]) -> TermResult {
+    shift_simplify(terms)
+}
 fn ror_bitblast(terms: &[TermVec]) -> TermVec {
     let (x, y) = (&terms[0], &terms[1]);
     assert_eq!(x.len(), y.len());
@@ -437,8 +525,11 @@ fn ror_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn ror_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().rotr(rotate_amount(terms[1].bv())))
+}
 
-define_core_op!(Ite, 3, sort: ite_sort, bitblast: ite_bitblast, cnf_encode: ite_cnf_encode, simplify: ite_simplify);
+define_core_op!(Ite, 3, sort: ite_sort, bitblast: ite_bitblast, cnf_encode: ite_cnf_encode, simplify: ite_simplify, eval: ite_eval);
 fn ite_sort(terms: &[Term]) -> Sort {
     terms[1].sort()
 }
@@ -491,15 +582,20 @@ fn ite_cnf_encode(dc: &mut DagCnf, terms: &[Lit]) -> Lit {
     dc.add_rel(l.var(), &LitVvec::cnf_ite(l, terms[0], terms[1], terms[2]));
     l
 }
+fn ite_eval(terms: &[Value]) -> Value {
+    if terms[0].bv()[0] {
+        terms[1].clone()
+    } else {
+        terms[2].clone()
+    }
+}
 
-define_core_op!(Concat, 2, sort: concat_sort, bitblast: concat_bitblast, simplify: concat_simplify);
+define_core_op!(Concat, 2, sort: concat_sort, bitblast: concat_bitblast, simplify: concat_simplify, eval: concat_eval);
 fn concat_simplify(terms: &[Term]) -> TermResult {
     let x = &terms[0];
     let y = &terms[1];
     if let (Some(xc), Some(yc)) = (x.try_bv_const(), y.try_bv_const()) {
-        let mut c = yc.to_vec();
-        c.extend_from_slice(xc);
-        return TermResult::Some(Term::bv_const(BvConst::new(&c)));
+        return TermResult::Some(Term::bv_const(xc.concat(yc)));
     }
     TermResult::None
 }
@@ -511,8 +607,17 @@ fn concat_bitblast(terms: &[TermVec]) -> TermVec {
     res.extend_from_slice(&terms[0]);
     res
 }
+fn concat_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().concat(terms[1].bv()))
+}
 
-define_core_op!(Sext, 2, sort: sext_sort, bitblast: sext_bitblast);
+define_core_op!(Sext, 2, sort: sext_sort, bitblast: sext_bitblast, simplify: sext_simplify, eval: sext_eval);
+fn sext_simplify(terms: &[Term]) -> TermResult {
+    if terms[1].bv_len() == 0 {
+        return TermResult::Some(terms[0].clone());
+    }
+    TermResult::None
+}
 fn sext_sort(terms: &[Term]) -> Sort {
     Sort::Bv(terms[0].bv_len() + terms[1].bv_len())
 }
@@ -523,9 +628,16 @@ fn sext_bitblast(terms: &[TermVec]) -> TermVec {
     res.extend(ext);
     res
 }
+fn sext_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().sext(terms[1].bv().len()))
+}
 
-define_core_op!(Slice, 3, sort: slice_sort, bitblast: slice_bitblast, simplify: slice_simplify);
-fn slice_simplify(_terms: &[Term]) -> TermResult {
+define_core_op!(Slice, 3, sort: slice_sort, bitblast: slice_bitblast, simplify: slice_simplify, eval: slice_eval);
+fn slice_simplify(terms: &[Term]) -> TermResult {
+    let x = &terms[0];
+    if terms[2].bv_len() == 0 && terms[1].bv_len() + 1 == x.bv_len() {
+        return TermResult::Some(x.clone());
+    }
     TermResult::None
 }
 fn slice_sort(terms: &[Term]) -> Sort {
@@ -536,11 +648,25 @@ fn slice_bitblast(terms: &[TermVec]) -> TermVec {
     let h = terms[1].len();
     terms[0][l..=h].iter().cloned().collect()
 }
+fn slice_eval(terms: &[Value]) -> Value {
+    let l = terms[2].bv().len();
+    let h = terms[1].bv().len();
+    Value::Bv(terms[0].bv().extract(l, h))
+}
 
-define_core_op!(Redxor, 1, sort: bool_sort, bitblast: redxor_bitblast);
+define_core_op!(Redxor, 1, sort: bool_sort, bitblast: redxor_bitblast, simplify: redxor_simplify, eval: redxor_eval);
+fn redxor_simplify(terms: &[Term]) -> TermResult {
+    if terms[0].is_bool() {
+        return TermResult::Some(terms[0].clone());
+    }
+    TermResult::None
+}
 fn redxor_bitblast(terms: &[TermVec]) -> TermVec {
     TermVec::from([Term::new_op_fold(Xor, terms[0].iter())])
 }
+fn redxor_eval(terms: &[Value]) -> Value {
+    bool_value(terms[0].bv().iter().fold(false, |r, b| r ^ b))
+}
 
 #[inline]
 fn full_adder(x: &Term, y: &Term, c: &Term) -> (Term, Term) {
@@ -552,19 +678,51 @@ fn full_adder(x: &Term, y: &Term, c: &Term) -> (Term, Term) {
     (r, c)
 }
 
-define_core_op!(Add, 2, bitblast: add_bitblast);
+define_core_op!(Add, 2, bitblast: add_bitblast, simplify: add_simplify, eval: add_eval);
+fn add_simplify(terms: &[Term]) -> TermResult {
+    let simp = |a: &Term, b: &Term| {
+        if a.try_bv_const().is_some_and(|c| c.is_zero()) {
+            return TermResult::Some(b.clone());
+        }
+        TermResult::None
+    };
+    simp(&terms[0], &terms[1])?;
+    simp(&terms[1], &terms[0])
+}
 fn add_bitblast(terms: &[TermVec]) -> TermVec {
+    add_carry(&terms[0], &terms[1], Term::bool_const(false)).0
+}
+
+#[inline]
+fn add_carry(x: &TermVec, y: &TermVec, mut c: Term) -> (TermVec, Term) {
     let mut r;
-    let mut c = Term::bool_const(false);
     let mut res = TermVec::new();
-    for (x, y) in terms[0].iter().zip(terms[1].iter()) {
+    for (x, y) in x.iter().zip(y.iter()) {
         (r, c) = full_adder(x, y, &c);
         res.push(r);
     }
-    res
+    (res, c)
+}
+fn add_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv() + terms[1].bv())
 }
 
-define_core_op!(Mul, 2, bitblast: mul_bitblast);
+define_core_op!(Mul, 2, bitblast: mul_bitblast, simplify: mul_simplify, eval: mul_eval);
+fn mul_simplify(terms: &[Term]) -> TermResult {
+    let simp = |a: &Term, b: &Term| {
+        if let Some(ac) = a.try_bv_const() {
+            if ac.is_zero() {
+                return TermResult::Some(a.clone());
+            }
+            if *ac == BvConst::one(ac.len()) {
+                return TermResult::Some(b.clone());
+            }
+        }
+        TermResult::None
+    };
+    simp(&terms[0], &terms[1])?;
+    simp(&terms[1], &terms[0])
+}
 fn mul_bitblast(terms: &[TermVec]) -> TermVec {
     let x = &terms[0];
     let y = &terms[1];
@@ -580,6 +738,9 @@ fn mul_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn mul_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv() * terms[1].bv())
+}
 
 fn scgate_co(r: &Term, d: &Term, ci: &Term) -> Term {
     let d_or_ci = d | ci;
@@ -619,18 +780,169 @@ fn udiv_urem_bitblast(a: &TermVec, din: &TermVec) -> (TermVec, TermVec) {
     (q, TermVec::from(s[size][1..=size].to_vec()))
 }
 
-define_core_op!(Udiv, 2, bitblast: udiv_bitblast);
+define_core_op!(Udiv, 2, bitblast: udiv_bitblast, simplify: udiv_simplify, eval: udiv_eval);
+fn udiv_simplify(terms: &[Term]) -> TermResult {
+    if let Some(yc) = terms[1].try_bv_const()
+        && *yc == BvConst::one(yc.len())
+    {
+        return TermResult::Some(terms[0].clone());
+    }
+    TermResult::None
+}
 fn udiv_bitblast(terms: &[TermVec]) -> TermVec {
     let (q, _) = udiv_urem_bitblast(&terms[0], &terms[1]);
     q
 }
-define_core_op!(Urem, 2, bitblast: urem_bitblast);
+fn udiv_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().udiv(terms[1].bv()))
+}
+define_core_op!(Urem, 2, bitblast: urem_bitblast, simplify: urem_simplify, eval: urem_eval);
+fn urem_simplify(terms: &[Term]) -> TermResult {
+    if let Some(yc) = terms[1].try_bv_const()
+        && *yc == BvConst::one(yc.len())
+    {
+        return TermResult::Some(terms[0].mk_bv_const_zero());
+    }
+    if terms[0] == terms[1] {
+        return TermResult::Some(terms[0].mk_bv_const_zero());
+    }
+    TermResult::None
+}
 fn urem_bitblast(terms: &[TermVec]) -> TermVec {
     let (_, r) = udiv_urem_bitblast(&terms[0], &terms[1]);
     r
 }
+fn urem_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].bv().urem(terms[1].bv()))
+}
+
+/// Signed overflow of `x op y` whose result is `r`, where `same` tells whether the
+/// operand signs must agree (addition) or differ (subtraction) for overflow.
+#[inline]
+fn signed_overflow(x: &TermVec, y: &TermVec, r: &TermVec, same: bool) -> TermVec {
+    let (sx, sy, sr) = (x.last().unwrap(), y.last().unwrap(), r.last().unwrap());
+    TermVec::from([(sx ^ sy).not_if(same) & (sx ^ sr)])
+}
 
-define_core_op!(Read, 2, sort: read_sort, bitblast: read_bitblast);
+define_core_op!(Uaddo, 2, sort: bool_sort, bitblast: uaddo_bitblast, eval: uaddo_eval);
+fn uaddo_bitblast(terms: &[TermVec]) -> TermVec {
+    let (_, c) = add_carry(&terms[0], &terms[1], Term::bool_const(false));
+    TermVec::from([c])
+}
+fn uaddo_eval(terms: &[Value]) -> Value {
+    bool_value(terms[0].bv().overflowing_add(terms[1].bv()).1)
+}
+
+define_core_op!(Saddo, 2, sort: bool_sort, bitblast: saddo_bitblast, eval: saddo_eval);
+fn saddo_bitblast(terms: &[TermVec]) -> TermVec {
+    let (r, _) = add_carry(&terms[0], &terms[1], Term::bool_const(false));
+    signed_overflow(&terms[0], &terms[1], &r, true)
+}
+fn saddo_eval(terms: &[Value]) -> Value {
+    let (x, y) = (terms[0].bv(), terms[1].bv());
+    bool_value(signed_overflow_value(x, y, &(x + y), true))
+}
+
+define_core_op!(Usubo, 2, sort: bool_sort, bitblast: usubo_bitblast, eval: usubo_eval);
+fn usubo_bitblast(terms: &[TermVec]) -> TermVec {
+    let ny: TermVec = terms[1].iter().map(|t| !t).collect();
+    let (_, c) = add_carry(&terms[0], &ny, Term::bool_const(true));
+    TermVec::from([!c])
+}
+fn usubo_eval(terms: &[Value]) -> Value {
+    bool_value(terms[0].bv().overflowing_sub(terms[1].bv()).1)
+}
+
+define_core_op!(Ssubo, 2, sort: bool_sort, bitblast: ssubo_bitblast, eval: ssubo_eval);
+fn ssubo_bitblast(terms: &[TermVec]) -> TermVec {
+    let ny: TermVec = terms[1].iter().map(|t| !t).collect();
+    let (r, _) = add_carry(&terms[0], &ny, Term::bool_const(true));
+    signed_overflow(&terms[0], &terms[1], &r, false)
+}
+fn ssubo_eval(terms: &[Value]) -> Value {
+    let (x, y) = (terms[0].bv(), terms[1].bv());
+    bool_value(signed_overflow_value(x, y, &(x - y), false))
+}
+
+/// Multiplies the operands extended to double width, zero or sign filled.
+fn wide_mul_bitblast(terms: &[TermVec], signed: bool) -> TermVec {
+    let ext = |x: &TermVec| -> TermVec {
+        let fill = if signed {
+            x.last().unwrap().clone()
+        } else {
+            Term::bool_const(false)
+        };
+        let mut x = x.clone();
+        let len = x.len();
+        x.extend(vec![fill; len]);
+        x
+    };
+    mul_bitblast(&[ext(&terms[0]), ext(&terms[1])])
+}
+
+define_core_op!(Umulo, 2, sort: bool_sort, bitblast: umulo_bitblast, eval: umulo_eval);
+fn umulo_bitblast(terms: &[TermVec]) -> TermVec {
+    let len = terms[0].len();
+    let r = wide_mul_bitblast(terms, false);
+    TermVec::from([Term::new_op_fold(Or, r[len..].iter())])
+}
+fn umulo_eval(terms: &[Value]) -> Value {
+    let (x, y) = (terms[0].bv(), terms[1].bv());
+    let len = x.len();
+    let r = &x.uext(len) * &y.uext(len);
+    bool_value(!r.extract(len, 2 * len - 1).is_zero())
+}
+
+define_core_op!(Smulo, 2, sort: bool_sort, bitblast: smulo_bitblast, eval: smulo_eval);
+fn smulo_bitblast(terms: &[TermVec]) -> TermVec {
+    let len = terms[0].len();
+    let r = wide_mul_bitblast(terms, true);
+    let s = &r[len - 1];
+    let diff: Vec<Term> = r[len..].iter().map(|t| t ^ s).collect();
+    TermVec::from([Term::new_op_fold(Or, diff.iter())])
+}
+fn smulo_eval(terms: &[Value]) -> Value {
+    let (x, y) = (terms[0].bv(), terms[1].bv());
+    let len = x.len();
+    let r = &x.sext(len) * &y.sext(len);
+    let high = r.extract(len - 1, 2 * len - 1);
+    bool_value(!high.is_zero() && !high.is_ones())
+}
+
+define_core_op!(Sdivo, 2, sort: bool_sort, bitblast: sdivo_bitblast, eval: sdivo_eval);
+fn sdivo_bitblast(terms: &[TermVec]) -> TermVec {
+    let (x, y) = (&terms[0], &terms[1]);
+    let len = x.len();
+    let min = x[..len - 1].iter().map(|t| !t).chain([x[len - 1].clone()]);
+    let neg_one = y.iter().cloned();
+    TermVec::from([Term::new_op_fold(And, min.chain(neg_one))])
+}
+fn sdivo_eval(terms: &[Value]) -> Value {
+    let (x, y) = (terms[0].bv(), terms[1].bv());
+    let min = BvConst::one(x.len()).shl(x.len() - 1);
+    bool_value(*x == min && y.is_ones())
+}
+
+define_core_op!(Read, 2, sort: read_sort, bitblast: read_bitblast, simplify: read_simplify, eval: read_eval);
+fn read_simplify(terms: &[Term]) -> TermResult {
+    let (mut array, index) = (&terms[0], &terms[1]);
+    // Skip the writes to other constant indices.
+    while let Some(w) = array.try_op()
+        && w.op == Write
+    {
+        if w[1] == *index {
+            return TermResult::Some(w[2].clone());
+        }
+        if !(w[1].is_const() && index.is_const()) {
+            break;
+        }
+        array = &w[0];
+    }
+    if array == &terms[0] {
+        return TermResult::None;
+    }
+    TermResult::Some(array.op1(Read, index))
+}
 fn read_sort(terms: &[Term]) -> Sort {
     let (_, e) = terms[0].sort().array();
     Sort::Bv(e)
@@ -642,12 +954,12 @@ fn onehot_encode(x: &[Term]) -> TermVec {
     res[0] = Term::bool_const(true);
     for (sb, shift) in x.iter().enumerate() {
         let ss = 1 << sb;
+        for j in (ss..len).rev() {
+            res[j] = shift.ite(&res[j - ss], &res[j]);
+        }
         for rj in &mut res[0..ss] {
             *rj = !shift & &rj;
         }
-        for j in ss..len {
-            res[j] = shift.ite(&res[j - ss], &res[j]);
-        }
     }
     TermVec::from(res.as_slice())
 }
@@ -669,8 +981,21 @@ fn read_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn read_eval(terms: &[Value]) -> Value {
+    Value::Bv(terms[0].array().read(terms[1].bv()))
+}
 
-define_core_op!(Write, 3, bitblast: write_bitblast);
+define_core_op!(Write, 3, bitblast: write_bitblast, simplify: write_simplify, eval: write_eval);
+fn write_simplify(terms: &[Term]) -> TermResult {
+    let (array, index, value) = (&terms[0], &terms[1], &terms[2]);
+    if let Some(w) = array.try_op()
+        && w.op == Write
+        && w[1] == *index
+    {
+        return TermResult::Some(w[0].op2(Write, index, value));
+    }
+    TermResult::None
+}
 fn write_bitblast(terms: &[TermVec]) -> TermVec {
     let (array, index, value) = (&terms[0], &terms[1], &terms[2]);
     let index_len = index.len();
@@ -687,3 +1012,82 @@ fn write_bitblast(terms: &[TermVec]) -> TermVec {
     }
     res
 }
+fn write_eval(terms: &[Value]) -> Value {
+    let mut array = terms[0].array().clone();
+    array.write(terms[1].bv().clone(), terms[2].bv().clone());
+    Value::Array(array)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        fol::{TEST_LOCK, op::check_binary_exhaustive},
+        satif::{CdclSolver, Satif},
+    };
+    use giputils::hash::GHashMap;
+
+    #[test]
+    fn test_read_write_bitblast() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let m = Term::new_var(Sort::Array(2, 3));
+        let i = Term::new_var(Sort::Bv(2));
+        let j = Term::new_var(Sort::Bv(2));
+        let v = Term::new_var(Sort::Bv(3));
+        let r = m.op2(Write, &i, &v).op1(Read, &j);
+        let mut bb = GHashMap::new();
+        let mut cm = GHashMap::new();
+        let mut dc = DagCnf::new();
+        let mut enc = |t: &Term| -> Vec<Lit> {
+            t.bitblast(&mut bb)
+                .iter()
+                .map(|b| b.cnf_encode(&mut dc, &mut cm))
+                .collect()
+        };
+        let (ml, il, jl, vl, rl) = (enc(&m), enc(&i), enc(&j), enc(&v), enc(&r));
+        let mut solver = CdclSolver::new();
+        for c in dc.clause() {
+            solver.add_clause(c);
+        }
+        let fix = |l: &[Lit], v: usize| -> Vec<Lit> {
+            l.iter()
+                .enumerate()
+                .map(|(b, l)| l.not_if(v >> b & 1 == 0))
+                .collect()
+        };
+        // Entry k of `m` holds k + 1, the written value is 7.
+        let mut mem = fix(&vl, 7);
+        for k in 0..4 {
+            mem.extend(fix(&ml[3 * k..3 * (k + 1)], k + 1));
+        }
+        for iv in 0..4 {
+            for jv in 0..4 {
+                let assumps = [mem.clone(), fix(&il, iv), fix(&jl, jv)].concat();
+                assert!(solver.solve(&assumps));
+                let got = rl.iter().enumerate().fold(0, |acc, (b, l)| {
+                    acc | (solver.sat_value(*l).unwrap() as usize) << b
+                });
+                assert_eq!(got, if iv == jv { 7 } else { jv + 1 }, "i={iv} j={jv}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for w in 1..=4 {
+            let (min, max) = (-(1i64 << (w - 1)), (1i64 << (w - 1)) - 1);
+            let m = 1i64 << w;
+            let s = |r: i64| (r < min || r > max) as i64;
+            let u = |r: i64| (r < 0 || r >= m) as i64;
+            let us = |a: i64| a.rem_euclid(m);
+            check_binary_exhaustive("saddo", w, |a, b| s(a + b));
+            check_binary_exhaustive("ssubo", w, |a, b| s(a - b));
+            check_binary_exhaustive("smulo", w, |a, b| s(a * b));
+            check_binary_exhaustive("sdivo", w, |a, b| (b != 0 && s(a / b) == 1) as i64);
+            check_binary_exhaustive("uaddo", w, |a, b| u(us(a) + us(b)));
+            check_binary_exhaustive("usubo", w, |a, b| u(us(a) - us(b)));
+            check_binary_exhaustive("umulo", w, |a, b| u(us(a) * us(b)));
+        }
+    }
+}
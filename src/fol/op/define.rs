@@ -31,6 +31,13 @@ macro_rules! op_trait_impl {
             $impl(terms)
         }
     };
+    (eval $impl:expr) => {
+        #[inline]
+        fn eval(&self, terms: &[crate::fol::Value]) -> Option<crate::fol::Value> {
+            debug_assert!(self.num_operand() == terms.len());
+            Some($impl(terms))
+        }
+    };
     (cnf_encode $impl:expr) => {
         #[inline]
         fn cnf_encode(&self, dc: &mut crate::DagCnf, terms: &[crate::Lit]) -> crate::Lit {
@@ -3,7 +3,7 @@ mod define;
 mod other_op;
 
 use super::term::Term;
-use crate::fol::{Sort, TermResult, TermVec};
+use crate::fol::{Sort, TermResult, TermVec, Value};
 use crate::{DagCnf, Lit};
 pub use core_op::*;
 use giputils::hash::GHashMap;
@@ -57,6 +57,11 @@ pub trait Op: Debug + 'static {
     fn cnf_encode(&self, _dc: &mut DagCnf, _terms: &[Lit]) -> Lit {
         panic!("{self:?} not support cnf_encode");
     }
+
+    /// `None` if the op does not support evaluation.
+    fn eval(&self, _terms: &[Value]) -> Option<Value> {
+        None
+    }
 }
 
 #[derive(Clone)]
@@ -136,30 +141,64 @@ lazy_static! {
     };
 }
 
+impl DynOp {
+    /// Looks up a registered op by its case-insensitive name.
+    #[inline]
+    pub fn get(name: &str) -> Option<Self> {
+        OP_MAP.get(&name.to_lowercase()).cloned()
+    }
+}
+
 impl From<&str> for DynOp {
     #[inline]
     fn from(value: &str) -> Self {
-        OP_MAP
-            .get(&value.to_lowercase())
-            .unwrap_or_else(|| panic!("unsupport {value} op!"))
-            .clone()
+        Self::get(value).unwrap_or_else(|| panic!("unsupport {value} op!"))
     }
 }
 
-// pub enum BiOpType {
-//     Rol,
-//     Ror,
-//     Sdiv,
-//     Udiv,
-//     Smod,
-//     Srem,
-//     Urem,
-//     Saddo,
-//     Uaddo,
-//     Sdivo,
-//     Udivo,
-//     Smulo,
-//     Umulo,
-//     Ssubo,
-//     Usubo,
-// }
+/// Checks `op` on every pair of signed `width`-bit operands by bitblasting it to SAT,
+/// and evaluating it, comparing against `reference` modulo the result width.
+#[cfg(test)]
+pub(crate) fn check_binary_exhaustive(op: &str, width: usize, reference: impl Fn(i64, i64) -> i64) {
+    use crate::fol::BvConst;
+    use crate::satif::{CdclSolver, Satif};
+    let x = Term::new_var(Sort::Bv(width));
+    let y = Term::new_var(Sort::Bv(width));
+    let r = x.op1(op, &y);
+    let mut bb = GHashMap::new();
+    let mut cm = GHashMap::new();
+    let mut dc = DagCnf::new();
+    let mut enc = |t: &Term| -> Vec<Lit> {
+        t.bitblast(&mut bb)
+            .iter()
+            .map(|b| b.cnf_encode(&mut dc, &mut cm))
+            .collect()
+    };
+    let (xl, yl, rl) = (enc(&x), enc(&y), enc(&r));
+    let mut solver = CdclSolver::new();
+    for c in dc.clause() {
+        solver.add_clause(c);
+    }
+    let bits = |l: &[Lit], v: i64| -> Vec<Lit> {
+        l.iter()
+            .enumerate()
+            .map(|(i, l)| l.not_if((v >> i) & 1 == 0))
+            .collect()
+    };
+    let h = 1i64 << (width - 1);
+    for a in -h..h {
+        for b in -h..h {
+            let assumps: Vec<Lit> = [bits(&xl, a), bits(&yl, b)].concat();
+            assert!(solver.solve(&assumps));
+            let v = rl.iter().enumerate().fold(0, |v, (i, l)| {
+                v | ((solver.sat_value(*l).unwrap() as i64) << i)
+            });
+            let expect = reference(a, b).rem_euclid(1 << rl.len());
+            assert_eq!(v, expect, "{op} {a} {b} width {width}");
+            let value = |v: i64| Value::Bv(BvConst::from_u64(v as u64, width));
+            let env = GHashMap::from_iter([(x.clone(), value(a)), (y.clone(), value(b))]);
+            let e = r.eval(&env).bv().to_u64().unwrap() as i64;
+            assert_eq!(e, expect, "eval {op} {a} {b} width {width}");
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::Term;
 use super::define::define_non_core_op;
-use super::{Concat, Eq, Slt, Ult, Xor};
+use super::{Concat, Eq, Slt, Udiv, Ult, Urem, Xor};
 
 define_non_core_op!(Neg, 1, neg_normalize);
 fn neg_normalize(terms: &[Term]) -> Term {
@@ -103,3 +103,62 @@ define_non_core_op!(Sub, 2, sub_normalize);
 fn sub_normalize(terms: &[Term]) -> Term {
     &terms[0] + -&terms[1]
 }
+
+/// Returns the sign bit and the magnitude of a two's complement term.
+fn sign_abs(x: &Term) -> (Term, Term) {
+    let w = x.bv_len();
+    let sign = x.slice(w - 1, w - 1);
+    let abs = sign.ite(&-x, x);
+    (sign, abs)
+}
+
+define_non_core_op!(Sdiv, 2, sdiv_normalize);
+fn sdiv_normalize(terms: &[Term]) -> Term {
+    let (sx, x) = sign_abs(&terms[0]);
+    let (sy, y) = sign_abs(&terms[1]);
+    let q = x.op1(Udiv, &y);
+    (sx ^ sy).ite(&-&q, &q)
+}
+
+define_non_core_op!(Srem, 2, srem_normalize);
+fn srem_normalize(terms: &[Term]) -> Term {
+    let (sx, x) = sign_abs(&terms[0]);
+    let (_, y) = sign_abs(&terms[1]);
+    let r = x.op1(Urem, &y);
+    sx.ite(&-&r, &r)
+}
+
+define_non_core_op!(Smod, 2, smod_normalize);
+fn smod_normalize(terms: &[Term]) -> Term {
+    let (sx, x) = sign_abs(&terms[0]);
+    let (sy, y) = sign_abs(&terms[1]);
+    let u = x.op1(Urem, &y);
+    let nu = -&u;
+    let pos = sy.ite(&(&u + &terms[1]), &u);
+    let neg = sy.ite(&nu, &(&nu + &terms[1]));
+    let zero = u.mk_bv_const_zero();
+    u.op1(Eq, &zero).ite(&u, &sx.ite(&neg, &pos))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fol::{TEST_LOCK, op::check_binary_exhaustive};
+
+    #[test]
+    fn test_signed_div() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for w in 1..=4 {
+            check_binary_exhaustive("sdiv", w, |a, b| match b {
+                0 if a < 0 => 1,
+                0 => -1,
+                _ => a / b,
+            });
+            check_binary_exhaustive("srem", w, |a, b| if b == 0 { a } else { a % b });
+            check_binary_exhaustive("smod", w, |a, b| match b {
+                0 => a,
+                _ if a % b != 0 && (a % b < 0) != (b < 0) => a % b + b,
+                _ => a % b,
+            });
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use super::{OpTerm, Term, TermType, op::DynOp, simplify::fold_const};
+use giputils::hash::{GHashMap, GHashSet};
+use std::ops::Deref;
+
+type Bindings = GHashMap<Term, Term>;
+
+/// Rewrites terms matching `lhs` into `rhs`, both built over the pattern
+/// variables `vars`, when `cond` holds for the subterms bound to `vars`.
+/// A pattern variable only matches terms of its own sort, and `lhs` is
+/// matched in the normalized form its non-core ops were built into.
+pub struct RewriteRule {
+    pub name: &'static str,
+    vars: Vec<Term>,
+    lhs: Term,
+    rhs: Term,
+    cond: Option<fn(&[Term]) -> bool>,
+}
+
+impl RewriteRule {
+    pub fn new(name: &'static str, vars: &[Term], lhs: Term, rhs: Term) -> Self {
+        assert!(
+            lhs.try_op().is_some(),
+            "rewrite rule {name} must match an op"
+        );
+        let rule = Self {
+            name,
+            vars: vars.to_vec(),
+            lhs,
+            rhs,
+            cond: None,
+        };
+        let mut b = Bindings::new();
+        rule.matches(&rule.lhs, &rule.lhs, &mut b);
+        assert!(
+            b.len() == rule.vars.len(),
+            "rewrite rule {name} has variables not in its pattern"
+        );
+        assert!(
+            rule.rhs.sort() == rule.lhs.sort(),
+            "rewrite rule {name} changes the sort"
+        );
+        assert!(
+            only_vars(&rule.rhs, &rule.vars),
+            "rewrite rule {name} rhs uses variables not in its pattern"
+        );
+        rule
+    }
+
+    /// Only rewrite when `cond` holds for the bound subterms, in the order of `vars`.
+    #[inline]
+    pub fn with_cond(mut self, cond: fn(&[Term]) -> bool) -> Self {
+        self.cond = Some(cond);
+        self
+    }
+
+    fn matches(&self, p: &Term, t: &Term, b: &mut Bindings) -> bool {
+        if self.vars.contains(p) {
+            if p.sort() != t.sort() {
+                return false;
+            }
+            return match b.get(p) {
+                Some(bound) => bound == t,
+                None => {
+                    b.insert(p.clone(), t.clone());
+                    true
+                }
+            };
+        }
+        match (p.try_op(), t.try_op()) {
+            (Some(po), Some(to)) => {
+                po.op == to.op
+                    && po.terms.len() == to.terms.len()
+                    && po
+                        .terms
+                        .iter()
+                        .zip(to.terms.iter())
+                        .all(|(p, t)| self.matches(p, t, b))
+            }
+            _ => p == t,
+        }
+    }
+
+    /// The rewritten term, if `t` matches this rule.
+    pub fn apply(&self, t: &Term) -> Option<Term> {
+        let mut b = Bindings::new();
+        if !self.matches(&self.lhs, t, &mut b) {
+            return None;
+        }
+        if let Some(cond) = self.cond {
+            let bound: Vec<Term> = self.vars.iter().map(|v| b[v].clone()).collect();
+            if !cond(&bound) {
+                return None;
+            }
+        }
+        Some(instantiate(&self.rhs, &b))
+    }
+}
+
+/// Whether every variable in `p` is one of `vars`.
+fn only_vars(p: &Term, vars: &[Term]) -> bool {
+    match p.deref() {
+        TermType::Var(_) => vars.contains(p),
+        TermType::Op(op) => op.terms.iter().all(|t| only_vars(t, vars)),
+        TermType::Const(_) => true,
+    }
+}
+
+fn instantiate(p: &Term, b: &Bindings) -> Term {
+    if let Some(t) = b.get(p) {
+        return t.clone();
+    }
+    match p.deref() {
+        TermType::Op(op) => {
+            let terms: Vec<Term> = op.terms.iter().map(|t| instantiate(t, b)).collect();
+            Term::new_op(op.op.clone(), &terms)
+        }
+        _ => p.clone(),
+    }
+}
+
+/// Registers a rewrite rule with every [`Rewriter::new`], e.g.
+/// `inventory::submit! { RewriteRuleCollect(my_rule) }`.
+pub struct RewriteRuleCollect(pub fn() -> RewriteRule);
+
+inventory::collect!(RewriteRuleCollect);
+
+/// Applies rewrite rules bottom-up until no rule matches, memoizing the
+/// rewritten terms. Rules are not applied to a term while rewriting its own
+/// rule output, so cyclic rules such as commutativity stop after one cycle.
+#[derive(Default)]
+pub struct Rewriter {
+    rules: Vec<RewriteRule>,
+    index: GHashMap<DynOp, Vec<usize>>,
+    memo: GHashMap<Term, Term>,
+    active: GHashSet<Term>,
+    cut: bool,
+}
+
+impl Rewriter {
+    /// A rewriter with all rules registered through [`RewriteRuleCollect`].
+    pub fn new() -> Self {
+        let mut rw = Self::default();
+        for r in inventory::iter::<RewriteRuleCollect> {
+            rw.add_rule(r.0());
+        }
+        rw
+    }
+
+    pub fn add_rule(&mut self, rule: RewriteRule) {
+        let op = rule.lhs.try_op().unwrap().op.clone();
+        self.index.entry(op).or_default().push(self.rules.len());
+        self.rules.push(rule);
+        self.memo.clear();
+    }
+
+    pub fn rewrite(&mut self, t: &Term) -> Term {
+        if let Some(res) = self.memo.get(t) {
+            return res.clone();
+        }
+        let TermType::Op(op) = t.deref() else {
+            return t.clone();
+        };
+        let outer = std::mem::take(&mut self.cut);
+        let res = self.rewrite_op(op);
+        // A result cut short by an active term depends on the rewrite in
+        // progress above it, it is only reusable once that has finished.
+        if !self.cut || self.active.is_empty() {
+            self.memo.insert(t.clone(), res.clone());
+        }
+        self.cut |= outer;
+        res
+    }
+
+    fn rewrite_op(&mut self, op: &OpTerm) -> Term {
+        let terms: Vec<Term> = op.terms.iter().map(|s| self.rewrite(s)).collect();
+        let new = fold_const(op, &terms).unwrap_or_else(|| Term::new_op(op.op.clone(), &terms));
+        if self.active.contains(&new) {
+            self.cut = true;
+            return new;
+        }
+        match self.apply_rules(&new) {
+            Some(r) => {
+                self.active.insert(new.clone());
+                let res = self.rewrite(&r);
+                self.active.remove(&new);
+                res
+            }
+            None => new,
+        }
+    }
+
+    fn apply_rules(&self, t: &Term) -> Option<Term> {
+        let op = t.try_op()?;
+        self.index
+            .get(&op.op)?
+            .iter()
+            .find_map(|r| self.rules[*r].apply(t))
+    }
+}
+
+impl Term {
+    #[inline]
+    pub fn rewrite(&self, rw: &mut Rewriter) -> Term {
+        rw.rewrite(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fol::{Sort, TEST_LOCK};
+
+    fn add_const_assoc() -> RewriteRule {
+        let x = Term::new_var(Sort::Bv(8));
+        let c1 = Term::new_var(Sort::Bv(8));
+        let c2 = Term::new_var(Sort::Bv(8));
+        RewriteRule::new(
+            "add_const_assoc",
+            &[x.clone(), c1.clone(), c2.clone()],
+            &x + &c1 + &c2,
+            &x + (&c1 + &c2),
+        )
+        .with_cond(|b| b[1].is_const() && b[2].is_const())
+    }
+
+    inventory::submit! { RewriteRuleCollect(add_const_assoc) }
+
+    #[test]
+    fn test_rewrite() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let x = Term::new_var(Sort::Bv(8));
+        let y = Term::new_var(Sort::Bv(8));
+        let c = |v| Term::bv_const_from_usize(v, 8);
+        let mut rw = Rewriter::new();
+        let t = &x + c(3) + c(4) + c(250);
+        assert_eq!(t.rewrite(&mut rw), &x + c(1));
+        assert_eq!((&x + &y + c(4)).rewrite(&mut rw), &x + &y + c(4));
+
+        let z = Term::new_var(Sort::Bv(4));
+        let t = &z + Term::bv_const_from_usize(3, 4) + Term::bv_const_from_usize(4, 4);
+        assert_eq!(t.rewrite(&mut rw), t);
+
+        let (a, b) = (Term::new_var(Sort::Bv(8)), Term::new_var(Sort::Bv(8)));
+        rw.add_rule(RewriteRule::new(
+            "xor_cancel",
+            &[a.clone(), b.clone()],
+            &a ^ &b ^ &b,
+            a.clone(),
+        ));
+        let t = (&x ^ c(5) ^ c(5)) + c(2) + c(2);
+        assert_eq!(t.rewrite(&mut rw), &x + c(4));
+        assert_eq!((&x ^ &y ^ &x).rewrite(&mut rw), &x ^ &y ^ &x);
+
+        rw.add_rule(RewriteRule::new(
+            "and_comm",
+            &[a.clone(), b.clone()],
+            &a & &b,
+            &b & &a,
+        ));
+        let res = (&x & &y).rewrite(&mut rw);
+        assert!(res == &x & &y || res == &y & &x);
+    }
+
+    #[test]
+    fn test_rewrite_cut_memo() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let x = Term::new_var(Sort::Bv(8));
+        let a = Term::new_var(Sort::Bv(8));
+        let mut rw = Rewriter::new();
+        rw.add_rule(RewriteRule::new(
+            "not_or",
+            std::slice::from_ref(&a),
+            !&a,
+            (&a | &a) + (&a | &a),
+        ));
+        rw.add_rule(RewriteRule::new(
+            "or_not",
+            std::slice::from_ref(&a),
+            &a | &a,
+            !&a,
+        ));
+        assert_eq!((!&x).rewrite(&mut rw), !&x + !&x);
+        // `x | x` was cut short at `!x` while rewriting `!x`, that result
+        // must not be reused.
+        assert_eq!((&x | &x).rewrite(&mut rw), !&x + !&x);
+    }
+
+    #[test]
+    #[should_panic(expected = "rhs uses variables not in its pattern")]
+    fn test_rule_unbound_rhs() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let (a, b) = (Term::new_var(Sort::Bv(8)), Term::new_var(Sort::Bv(8)));
+        RewriteRule::new("unbound", std::slice::from_ref(&a), !&a, b);
+    }
+}
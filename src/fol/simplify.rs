@@ -1,4 +1,4 @@
-use super::{Term, TermResult};
+use super::{OpTerm, Term, TermResult, Value};
 use giputils::hash::GHashMap;
 
 impl Term {
@@ -8,8 +8,10 @@ impl Term {
         }
         let simp = if let Some(op_term) = self.try_op() {
             let terms: Vec<Term> = op_term.terms.iter().map(|s| s.simplify(map)).collect();
-            if let TermResult::Some(new) = op_term.op.simplify(&terms) {
-                new
+            if let Some(c) = fold_const(op_term, &terms) {
+                c
+            } else if let TermResult::Some(new) = op_term.op.simplify(&terms) {
+                new.simplify(map)
             } else {
                 Term::new_op(op_term.op.clone(), &terms)
             }
@@ -20,3 +22,60 @@ impl Term {
         map.get(self).unwrap().clone()
     }
 }
+
+/// Evaluates an op whose operands are all constants, if the op supports it.
+pub(super) fn fold_const(op_term: &OpTerm, terms: &[Term]) -> Option<Term> {
+    let values: Vec<Value> = terms
+        .iter()
+        .map(|t| t.try_bv_const().map(|c| Value::Bv(c.clone())))
+        .collect::<Option<_>>()?;
+    match op_term.op.eval(&values)? {
+        Value::Bv(c) => Some(Term::bv_const(c)),
+        Value::Array(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fol::{
+        BvConst, Sort, TEST_LOCK,
+        op::{Mul, Read, Sext, Sll, Sra, Udiv, Urem, Write},
+    };
+
+    #[test]
+    fn test_simplify() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let c = |v: u64| Term::bv_const(BvConst::from_u64(v, 8));
+        let x = Term::new_var(Sort::Bv(8));
+        let m = Term::new_var(Sort::Array(8, 8));
+        let mut map = GHashMap::new();
+        let folded = (c(7).op1(Mul, &c(9)) + c(200))
+            .op1(Udiv, &c(5))
+            .op1(Sll, &c(1))
+            .op1(Sra, &c(2));
+        let expect = folded.eval(&GHashMap::new());
+        assert_eq!(folded.simplify(&mut map).try_bv_const(), Some(expect.bv()));
+        assert_eq!(c(9).op1(Urem, &c(0)).simplify(&mut map), c(9));
+        assert_eq!((&x + c(0)).simplify(&mut map), x);
+        assert_eq!(c(1).op1(Mul, &x).simplify(&mut map), x);
+        assert_eq!(x.op1(Mul, &c(0)).simplify(&mut map), c(0));
+        assert_eq!(x.op1(Sra, &c(0)).simplify(&mut map), x);
+        let ext = x.op1(Sext, &Term::bv_const_zero(0));
+        assert_eq!(ext.simplify(&mut map), x);
+        let w = m.op2(Write, &c(3), &x);
+        assert_eq!(w.op1(Read, &c(3)).simplify(&mut map), x);
+        let r = m.op1(Read, &c(4));
+        assert_eq!(w.op1(Read, &c(4)).simplify(&mut map), r);
+        let y = Term::new_var(Sort::Bv(8));
+        assert!(w.op1(Read, &y).simplify(&mut map).try_op().is_some());
+        let ww = w.op2(Write, &c(5), &y);
+        assert_eq!(ww.op1(Read, &c(3)).simplify(&mut map), x);
+        assert_eq!(ww.op1(Read, &c(4)).simplify(&mut map), r);
+        let wy = m.op2(Write, &y, &x).op2(Write, &c(5), &y);
+        assert_eq!(
+            wy.op1(Read, &c(3)).simplify(&mut map),
+            m.op2(Write, &y, &x).op1(Read, &c(3))
+        );
+    }
+}
@@ -0,0 +1,894 @@
+use super::{BvConst, OpTerm, Sort, Term, TermType, op::DynOp};
+use crate::dimacs::open_compressed;
+use giputils::hash::GHashMap;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::{self, Read, Write},
+    ops::Deref,
+    path::Path,
+};
+
+/// Operands that are width parameters rather than terms.
+#[inline]
+fn num_param(op: &OpTerm) -> usize {
+    match op.op.name() {
+        "Sext" => 1,
+        "Slice" => 2,
+        _ => 0,
+    }
+}
+
+/// Prints terms in SMT-LIB v2. Every term of sort `Bv(1)` is printed as
+/// `(_ BitVec 1)`, predicates are converted with `ite`. Variable `k` is named
+/// `v{k}`.
+#[derive(Default)]
+pub struct Smt2Printer {
+    names: GHashMap<Term, String>,
+    num_let: usize,
+}
+
+impl Smt2Printer {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn bool_of(bv: String) -> String {
+        format!("(= {bv} #b1)")
+    }
+
+    fn bv_of(pred: String) -> String {
+        format!("(ite {pred} #b1 #b0)")
+    }
+
+    fn op_expr(&self, t: &Term, o: &OpTerm) -> String {
+        let args: Vec<String> = o.terms[..o.terms.len() - num_param(o)]
+            .iter()
+            .map(|t| self.expr(t))
+            .collect();
+        let binary = |name: &str| format!("({name} {} {})", args[0], args[1]);
+        let rotate = |l: &str, r: &str| {
+            let w = o.terms[0].bv_len();
+            let w = format!("(_ bv{w} {w})");
+            let s = format!("(bvurem {} {w})", args[1]);
+            format!(
+                "(bvor ({l} {a} {s}) ({r} {a} (bvsub {w} {s})))",
+                a = args[0]
+            )
+        };
+        match o.op.name() {
+            "Not" => format!("(bvnot {})", args[0]),
+            "And" => binary("bvand"),
+            "Or" => binary("bvor"),
+            "Xor" => binary("bvxor"),
+            "Add" => binary("bvadd"),
+            "Mul" => binary("bvmul"),
+            "Udiv" => binary("bvudiv"),
+            "Urem" => binary("bvurem"),
+            "Sll" => binary("bvshl"),
+            "Srl" => binary("bvlshr"),
+            "Sra" => binary("bvashr"),
+            "Concat" => binary("concat"),
+            "Read" => binary("select"),
+            "Eq" => Self::bv_of(binary("=")),
+            "Ult" => Self::bv_of(binary("bvult")),
+            "Slt" => Self::bv_of(binary("bvslt")),
+            "Uaddo" => Self::bv_of(binary("bvuaddo")),
+            "Saddo" => Self::bv_of(binary("bvsaddo")),
+            "Usubo" => Self::bv_of(binary("bvusubo")),
+            "Ssubo" => Self::bv_of(binary("bvssubo")),
+            "Umulo" => Self::bv_of(binary("bvumulo")),
+            "Smulo" => Self::bv_of(binary("bvsmulo")),
+            "Sdivo" => Self::bv_of(binary("bvsdivo")),
+            "Rol" => rotate("bvshl", "bvlshr"),
+            "Ror" => rotate("bvlshr", "bvshl"),
+            "Ite" => format!(
+                "(ite {} {} {})",
+                Self::bool_of(args[0].clone()),
+                args[1],
+                args[2]
+            ),
+            "Write" => format!("(store {} {} {})", args[0], args[1], args[2]),
+            "Sext" => format!("((_ sign_extend {}) {})", o.terms[1].bv_len(), args[0]),
+            "Slice" => format!(
+                "((_ extract {} {}) {})",
+                o.terms[1].bv_len(),
+                o.terms[2].bv_len(),
+                args[0]
+            ),
+            "Redxor" => {
+                let w = o.terms[0].bv_len();
+                if w == 1 {
+                    return args[0].clone();
+                }
+                let bits: Vec<String> = (0..w)
+                    .map(|i| format!("((_ extract {i} {i}) {})", args[0]))
+                    .collect();
+                format!("(bvxor {})", bits.join(" "))
+            }
+            _ => panic!("{:?} not support smtlib2", t),
+        }
+    }
+
+    /// The expression of `t`, using the names bound so far.
+    pub fn expr(&self, t: &Term) -> String {
+        if let Some(n) = self.names.get(t) {
+            return n.clone();
+        }
+        match t.deref() {
+            TermType::Const(c) => c.to_string(),
+            TermType::Var(v) => format!("v{v}"),
+            TermType::Op(o) => self.op_expr(t, o),
+        }
+    }
+
+    /// Prints `t` with a `let` binding for every op subterm referenced more
+    /// than once.
+    pub fn term(&mut self, t: &Term) -> String {
+        let mut cnt: GHashMap<Term, usize> = GHashMap::new();
+        let mut order = Vec::new();
+        let mut stack = vec![(t.clone(), false)];
+        while let Some((t, done)) = stack.pop() {
+            if done {
+                order.push(t);
+                continue;
+            }
+            let c = cnt.entry(t.clone()).or_default();
+            *c += 1;
+            if *c > 1 || self.names.contains_key(&t) {
+                continue;
+            }
+            if let Some(o) = t.try_op() {
+                stack.push((t.clone(), true));
+                let n = o.terms.len() - num_param(o);
+                for s in o.terms[..n].iter().rev() {
+                    stack.push((s.clone(), false));
+                }
+            }
+        }
+        let mut binds = Vec::new();
+        for s in order {
+            if cnt[&s] > 1 && s != *t {
+                let e = self.expr(&s);
+                self.num_let += 1;
+                let name = format!("_s{}", self.num_let);
+                self.names.insert(s.clone(), name.clone());
+                binds.push((s, name, e));
+            }
+        }
+        let mut res = self.expr(t);
+        for (s, name, e) in binds.into_iter().rev() {
+            res = format!("(let (({name} {e})) {res})");
+            self.names.remove(&s);
+        }
+        res
+    }
+}
+
+/// Collects the variables below `terms`, ordered by id.
+pub fn term_vars<'a>(terms: impl IntoIterator<Item = &'a Term>) -> Vec<Term> {
+    let mut visit: GHashMap<Term, ()> = GHashMap::new();
+    let mut stack: Vec<Term> = terms.into_iter().cloned().collect();
+    let mut vars = Vec::new();
+    while let Some(t) = stack.pop() {
+        if visit.insert(t.clone(), ()).is_some() {
+            continue;
+        }
+        match t.deref() {
+            TermType::Var(v) => vars.push((*v, t.clone())),
+            TermType::Op(o) => stack.extend(o.terms.iter().cloned()),
+            TermType::Const(_) => (),
+        }
+    }
+    vars.sort_by_key(|(v, _)| *v);
+    vars.into_iter().map(|(_, t)| t).collect()
+}
+
+/// Writes a script asserting every term of `assertions`, which must be of
+/// sort `Bv(1)`. The logic is `QF_ABV` if an array occurs and `QF_BV`
+/// otherwise.
+pub fn write_smtlib2<W: Write>(assertions: &[Term], mut out: W) -> io::Result<()> {
+    let vars = term_vars(assertions);
+    let array = vars.iter().any(|v| v.sort().is_array());
+    writeln!(
+        out,
+        "(set-logic {})",
+        if array { "QF_ABV" } else { "QF_BV" }
+    )?;
+    let mut printer = Smt2Printer::new();
+    for v in vars.iter() {
+        writeln!(out, "(declare-fun {} () {})", printer.expr(v), v.sort())?;
+    }
+    for a in assertions {
+        assert!(a.is_bool());
+        writeln!(out, "(assert {})", Smt2Printer::bool_of(printer.term(a)))?;
+    }
+    writeln!(out, "(check-sat)")
+}
+
+#[derive(Debug, Clone)]
+enum SExpr {
+    Atom(usize, String),
+    List(usize, Vec<SExpr>),
+}
+
+impl SExpr {
+    #[inline]
+    fn line(&self) -> usize {
+        match self {
+            SExpr::Atom(l, _) | SExpr::List(l, _) => *l,
+        }
+    }
+
+    #[inline]
+    fn atom(&self) -> Option<&str> {
+        match self {
+            SExpr::Atom(_, a) => Some(a),
+            SExpr::List(..) => None,
+        }
+    }
+}
+
+impl Display for SExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SExpr::Atom(_, a) => write!(f, "{a}"),
+            SExpr::List(_, l) => {
+                write!(f, "(")?;
+                for (i, e) in l.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{e}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Smt2ErrorKind {
+    Io(io::Error),
+    Syntax(String),
+    UnknownSymbol(String),
+    Unsupported(String),
+    SortMismatch(String),
+}
+
+#[derive(Debug)]
+pub struct Smt2Error {
+    pub line: usize,
+    pub kind: Smt2ErrorKind,
+}
+
+impl Display for Smt2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "smtlib2 {}: ", self.line)?;
+        match &self.kind {
+            Smt2ErrorKind::Io(e) => write!(f, "io error: {e}"),
+            Smt2ErrorKind::Syntax(t) => write!(f, "invalid expression `{t}`"),
+            Smt2ErrorKind::UnknownSymbol(t) => write!(f, "unknown symbol `{t}`"),
+            Smt2ErrorKind::Unsupported(t) => write!(f, "unsupported `{t}`"),
+            Smt2ErrorKind::SortMismatch(t) => write!(f, "sort mismatch in `{t}`"),
+        }
+    }
+}
+
+impl Error for Smt2Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            Smt2ErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<SExpr>, Smt2Error> {
+    let mut stack: Vec<(usize, Vec<SExpr>)> = vec![(1, Vec::new())];
+    let mut line = 1;
+    let mut chars = s.chars().peekable();
+    let unterminated = |line| Smt2Error {
+        line,
+        kind: Smt2ErrorKind::Syntax("unterminated".to_string()),
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => (),
+            ';' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '(' => stack.push((line, Vec::new())),
+            ')' => {
+                let (l, list) = stack.pop().unwrap();
+                let Some(top) = stack.last_mut() else {
+                    return Err(Smt2Error {
+                        line,
+                        kind: Smt2ErrorKind::Syntax(")".to_string()),
+                    });
+                };
+                top.1.push(SExpr::List(l, list));
+            }
+            '|' | '"' => {
+                let start = line;
+                let mut atom = String::new();
+                if c == '"' {
+                    atom.push(c);
+                }
+                loop {
+                    match chars.next() {
+                        None => return Err(unterminated(start)),
+                        Some(e) if e == c => {
+                            if c == '"' && chars.next_if_eq(&'"').is_some() {
+                                atom.push(c);
+                                continue;
+                            }
+                            break;
+                        }
+                        Some(e) => {
+                            line += (e == '\n') as usize;
+                            atom.push(e);
+                        }
+                    }
+                }
+                if c == '"' {
+                    atom.push(c);
+                }
+                stack.last_mut().unwrap().1.push(SExpr::Atom(start, atom));
+            }
+            c => {
+                let mut atom = String::from(c);
+                while let Some(c) = chars
+                    .next_if(|c| !c.is_whitespace() && !matches!(c, '(' | ')' | ';' | '|' | '"'))
+                {
+                    atom.push(c);
+                }
+                stack.last_mut().unwrap().1.push(SExpr::Atom(line, atom));
+            }
+        }
+    }
+    if stack.len() > 1 {
+        return Err(unterminated(stack.last().unwrap().0));
+    }
+    Ok(stack.pop().unwrap().1)
+}
+
+/// Declared variables and assertions of an SMT-LIB2 script.
+#[derive(Debug, Clone, Default)]
+pub struct Smt2Script {
+    pub vars: Vec<(String, Term)>,
+    pub assertions: Vec<Term>,
+}
+
+struct DefineFun {
+    params: Vec<(String, Sort)>,
+    sort: Sort,
+    body: SExpr,
+}
+
+#[derive(Default)]
+struct Smt2Parser {
+    vars: GHashMap<String, Term>,
+    funs: GHashMap<String, DefineFun>,
+    scope: Vec<GHashMap<String, Term>>,
+}
+
+impl Smt2Parser {
+    fn error(e: &SExpr, kind: impl Fn(String) -> Smt2ErrorKind) -> Smt2Error {
+        Smt2Error {
+            line: e.line(),
+            kind: kind(e.to_string()),
+        }
+    }
+
+    fn num(e: &SExpr) -> Result<usize, Smt2Error> {
+        e.atom()
+            .and_then(|a| a.parse().ok())
+            .ok_or_else(|| Self::error(e, Smt2ErrorKind::Syntax))
+    }
+
+    fn symbol(e: &SExpr) -> Result<&str, Smt2Error> {
+        e.atom()
+            .filter(|a| !a.starts_with(['"', ':', '#']))
+            .ok_or_else(|| Self::error(e, Smt2ErrorKind::Syntax))
+    }
+
+    fn sort(e: &SExpr) -> Result<Sort, Smt2Error> {
+        let bv = |e: &SExpr| match e {
+            SExpr::List(_, l) if l.len() == 3 && l[0].atom() == Some("_") => {
+                if l[1].atom() != Some("BitVec") {
+                    return Err(Self::error(e, Smt2ErrorKind::Unsupported));
+                }
+                let w = Self::num(&l[2])?;
+                if w == 0 {
+                    return Err(Self::error(e, Smt2ErrorKind::Syntax));
+                }
+                Ok(w)
+            }
+            SExpr::Atom(_, a) if a == "Bool" => Ok(1),
+            _ => Err(Self::error(e, Smt2ErrorKind::Unsupported)),
+        };
+        match e {
+            SExpr::List(_, l) if l.len() == 3 && l[0].atom() == Some("Array") => {
+                Ok(Sort::Array(bv(&l[1])?, bv(&l[2])?))
+            }
+            _ => bv(e).map(Sort::Bv),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Term> {
+        self.scope
+            .iter()
+            .rev()
+            .find_map(|s| s.get(name))
+            .or_else(|| self.vars.get(name))
+            .cloned()
+    }
+
+    fn constant(e: &SExpr, a: &str) -> Option<Term> {
+        let digits = |s: &str, radix, len| {
+            BvConst::from_str_radix(s, radix, len).filter(|_| !s.starts_with('-'))
+        };
+        let c = if let Some(b) = a.strip_prefix("#b") {
+            digits(b, 2, b.len())?
+        } else if let Some(h) = a.strip_prefix("#x") {
+            digits(h, 16, h.len() * 4)?
+        } else {
+            match a {
+                "true" => return Some(Term::bool_const(true)),
+                "false" => return Some(Term::bool_const(false)),
+                _ => {
+                    let SExpr::List(_, l) = e else {
+                        return None;
+                    };
+                    let [_, v, w] = l.as_slice() else {
+                        return None;
+                    };
+                    let v = v.atom()?.strip_prefix("bv")?;
+                    let w = w.atom()?.parse().ok().filter(|w| *w > 0)?;
+                    digits(v, 10, w)?
+                }
+            }
+        };
+        (c.len() > 0).then(|| Term::bv_const(c))
+    }
+
+    fn apply_fun(&mut self, e: &SExpr, name: &str, args: Vec<Term>) -> Result<Term, Smt2Error> {
+        let fun = &self.funs[name];
+        if fun.params.len() != args.len()
+            || fun
+                .params
+                .iter()
+                .zip(args.iter())
+                .any(|((_, s), a)| a.sort() != *s)
+        {
+            return Err(Self::error(e, Smt2ErrorKind::SortMismatch));
+        }
+        let scope = fun
+            .params
+            .iter()
+            .map(|(n, _)| n.clone())
+            .zip(args)
+            .collect();
+        let (body, sort) = (fun.body.clone(), fun.sort);
+        let saved = std::mem::replace(&mut self.scope, vec![scope]);
+        let res = self.term(&body);
+        self.scope = saved;
+        let res = res?;
+        if res.sort() != sort {
+            return Err(Self::error(e, Smt2ErrorKind::SortMismatch));
+        }
+        Ok(res)
+    }
+
+    fn term(&mut self, e: &SExpr) -> Result<Term, Smt2Error> {
+        match e {
+            SExpr::Atom(_, a) => {
+                if let Some(t) = self.lookup(a).or_else(|| Self::constant(e, a)) {
+                    return Ok(t);
+                }
+                if self.funs.contains_key(a.as_str()) {
+                    return self.apply_fun(e, a, Vec::new());
+                }
+                Err(Self::error(e, Smt2ErrorKind::UnknownSymbol))
+            }
+            SExpr::List(_, l) if l.is_empty() => Err(Self::error(e, Smt2ErrorKind::Syntax)),
+            SExpr::List(_, l) if l[0].atom() == Some("_") => {
+                Self::constant(e, "").ok_or_else(|| Self::error(e, Smt2ErrorKind::Syntax))
+            }
+            SExpr::List(_, l) if l[0].atom() == Some("let") => {
+                let (Some(SExpr::List(_, binds)), Some(body), 3) = (l.get(1), l.get(2), l.len())
+                else {
+                    return Err(Self::error(e, Smt2ErrorKind::Syntax));
+                };
+                let mut scope = GHashMap::new();
+                for b in binds {
+                    let SExpr::List(_, b) = b else {
+                        return Err(Self::error(b, Smt2ErrorKind::Syntax));
+                    };
+                    let [n, t] = b.as_slice() else {
+                        return Err(Self::error(e, Smt2ErrorKind::Syntax));
+                    };
+                    scope.insert(Self::symbol(n)?.to_string(), self.term(t)?);
+                }
+                self.scope.push(scope);
+                let res = self.term(body);
+                self.scope.pop();
+                res
+            }
+            SExpr::List(_, l) => {
+                let args = l[1..]
+                    .iter()
+                    .map(|a| self.term(a))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let SExpr::List(_, f) = &l[0] {
+                    return self.indexed(e, f, args);
+                }
+                let f = Self::symbol(&l[0])?;
+                if self.funs.contains_key(f) {
+                    return self.apply_fun(e, f, args);
+                }
+                self.apply(e, f, args)
+            }
+        }
+    }
+
+    fn indexed(&self, e: &SExpr, f: &[SExpr], args: Vec<Term>) -> Result<Term, Smt2Error> {
+        let syntax = || Self::error(e, Smt2ErrorKind::Syntax);
+        if f.len() < 3 || f[0].atom() != Some("_") || args.len() != 1 {
+            return Err(syntax());
+        }
+        let x = &args[0];
+        if x.sort().is_array() {
+            return Err(Self::error(e, Smt2ErrorKind::SortMismatch));
+        }
+        let w = x.bv_len();
+        let n = Self::num(&f[2])?;
+        match (f[1].atom(), f.len()) {
+            (Some("extract"), 4) => {
+                let l = Self::num(&f[3])?;
+                if l > n || n >= w {
+                    return Err(syntax());
+                }
+                Ok(x.slice(l, n))
+            }
+            (Some("zero_extend"), 3) => Ok(x.op1("uext", &Term::bv_const_zero(n))),
+            (Some("sign_extend"), 3) => Ok(x.op1("sext", &Term::bv_const_zero(n))),
+            (Some("repeat"), 3) if n > 0 => Ok(Term::new_op_fold("concat", vec![x; n])),
+            (Some(r @ ("rotate_left" | "rotate_right")), 3) => {
+                let op = if r == "rotate_left" { "rol" } else { "ror" };
+                let n = BvConst::from_u64((n % w) as u64, w);
+                Ok(x.op1(op, &Term::bv_const(n)))
+            }
+            _ => Err(Self::error(e, Smt2ErrorKind::Unsupported)),
+        }
+    }
+
+    fn apply(&self, e: &SExpr, f: &str, args: Vec<Term>) -> Result<Term, Smt2Error> {
+        let mismatch = || Self::error(e, Smt2ErrorKind::SortMismatch);
+        let same_bv = |args: &[Term]| {
+            args.iter()
+                .all(|a| !a.sort().is_array() && a.sort() == args[0].sort())
+        };
+        let arity = |n: usize| {
+            if args.len() == n {
+                Ok(())
+            } else {
+                Err(Self::error(e, Smt2ErrorKind::Syntax))
+            }
+        };
+        let chain = |op: &str| -> Result<Term, Smt2Error> {
+            if args.len() < 2 || args.iter().any(|a| a.sort() != args[0].sort()) {
+                return Err(mismatch());
+            }
+            let eqs: Vec<Term> = args.windows(2).map(|w| w[0].op1(op, &w[1])).collect();
+            Ok(Term::new_op_fold("and", eqs))
+        };
+        let op = match f {
+            "=" => return chain("eq"),
+            "distinct" => {
+                if args.len() < 2 || args.iter().any(|a| a.sort() != args[0].sort()) {
+                    return Err(mismatch());
+                }
+                let mut neqs = Vec::new();
+                for i in 0..args.len() {
+                    for j in i + 1..args.len() {
+                        neqs.push(args[i].op1("neq", &args[j]));
+                    }
+                }
+                return Ok(Term::new_op_fold("and", neqs));
+            }
+            "ite" => {
+                arity(3)?;
+                if !args[0].is_bool() || args[1].sort() != args[2].sort() {
+                    return Err(mismatch());
+                }
+                return Ok(args[0].ite(&args[1], &args[2]));
+            }
+            "=>" => {
+                if args.len() < 2 || !args.iter().all(|a| a.is_bool()) {
+                    return Err(mismatch());
+                }
+                let mut res = args[args.len() - 1].clone();
+                for a in args[..args.len() - 1].iter().rev() {
+                    res = a.op1("implies", &res);
+                }
+                return Ok(res);
+            }
+            "select" => {
+                arity(2)?;
+                let Sort::Array(i, _) = args[0].sort() else {
+                    return Err(mismatch());
+                };
+                if args[1].sort() != Sort::Bv(i) {
+                    return Err(mismatch());
+                }
+                return Ok(args[0].op1("read", &args[1]));
+            }
+            "store" => {
+                arity(3)?;
+                let Sort::Array(i, v) = args[0].sort() else {
+                    return Err(mismatch());
+                };
+                if args[1].sort() != Sort::Bv(i) || args[2].sort() != Sort::Bv(v) {
+                    return Err(mismatch());
+                }
+                return Ok(args[0].op2("write", &args[1], &args[2]));
+            }
+            "concat" => {
+                if args.len() < 2 || args.iter().any(|a| a.sort().is_array()) {
+                    return Err(mismatch());
+                }
+                return Ok(Term::new_op_fold("concat", &args));
+            }
+            "not" | "bvnot" => "not",
+            "bvneg" => "neg",
+            "and" | "bvand" => "and",
+            "or" | "bvor" => "or",
+            "xor" | "bvxor" => "xor",
+            "bvadd" => "add",
+            "bvmul" => "mul",
+            "bvnand" => "nand",
+            "bvnor" => "nor",
+            "bvxnor" => "xnor",
+            "bvsub" => "sub",
+            "bvudiv" => "udiv",
+            "bvurem" => "urem",
+            "bvsdiv" => "sdiv",
+            "bvsrem" => "srem",
+            "bvsmod" => "smod",
+            "bvshl" => "sll",
+            "bvlshr" => "srl",
+            "bvashr" => "sra",
+            "bvcomp" => "eq",
+            "bvult" => "ult",
+            "bvule" => "ulte",
+            "bvugt" => "ugt",
+            "bvuge" => "ugte",
+            "bvslt" => "slt",
+            "bvsle" => "slte",
+            "bvsgt" => "sgt",
+            "bvsge" => "sgte",
+            "bvuaddo" => "uaddo",
+            "bvsaddo" => "saddo",
+            "bvusubo" => "usubo",
+            "bvssubo" => "ssubo",
+            "bvumulo" => "umulo",
+            "bvsmulo" => "smulo",
+            "bvsdivo" => "sdivo",
+            _ => return Err(Self::error(e, Smt2ErrorKind::UnknownSymbol)),
+        };
+        let op = DynOp::get(op).ok_or_else(|| Self::error(e, Smt2ErrorKind::Unsupported))?;
+        if args.is_empty() || !same_bv(&args) {
+            return Err(mismatch());
+        }
+        if matches!(f, "and" | "or" | "xor" | "not") && !args[0].is_bool() {
+            return Err(mismatch());
+        }
+        if op.num_operand() == 2
+            && args.len() > 2
+            && matches!(
+                f,
+                "and" | "or" | "xor" | "bvand" | "bvor" | "bvxor" | "bvadd" | "bvmul"
+            )
+        {
+            return Ok(args[1..].iter().fold(args[0].clone(), |acc, x| {
+                Term::new_op(op.clone(), [&acc, x])
+            }));
+        }
+        arity(op.num_operand())?;
+        Ok(Term::new_op(op, &args))
+    }
+
+    fn command(&mut self, e: &SExpr, script: &mut Smt2Script) -> Result<(), Smt2Error> {
+        let syntax = || Self::error(e, Smt2ErrorKind::Syntax);
+        let SExpr::List(_, l) = e else {
+            return Err(syntax());
+        };
+        let Some(cmd) = l.first().and_then(|c| c.atom()) else {
+            return Err(syntax());
+        };
+        match (cmd, &l[1..]) {
+            ("declare-fun", [n, SExpr::List(_, params), s]) if params.is_empty() => {
+                self.declare(n, s, script)
+            }
+            ("declare-const", [n, s]) => self.declare(n, s, script),
+            ("define-fun", [n, SExpr::List(_, params), s, body]) => {
+                let name = Self::symbol(n)?.to_string();
+                let mut ps = Vec::new();
+                for p in params {
+                    match p {
+                        SExpr::List(_, p) if p.len() == 2 => {
+                            ps.push((Self::symbol(&p[0])?.to_string(), Self::sort(&p[1])?));
+                        }
+                        _ => return Err(syntax()),
+                    }
+                }
+                let fun = DefineFun {
+                    params: ps,
+                    sort: Self::sort(s)?,
+                    body: body.clone(),
+                };
+                if fun.params.is_empty() {
+                    let saved = std::mem::take(&mut self.scope);
+                    let t = self.term(body);
+                    self.scope = saved;
+                    let t = t?;
+                    if t.sort() != fun.sort {
+                        return Err(Self::error(e, Smt2ErrorKind::SortMismatch));
+                    }
+                    self.vars.insert(name, t);
+                } else {
+                    self.funs.insert(name, fun);
+                }
+                Ok(())
+            }
+            ("assert", [t]) => {
+                let t = self.term(t)?;
+                if !t.is_bool() {
+                    return Err(Self::error(e, Smt2ErrorKind::SortMismatch));
+                }
+                script.assertions.push(t);
+                Ok(())
+            }
+            (
+                "set-logic" | "set-info" | "set-option" | "check-sat" | "get-model" | "get-value"
+                | "get-info" | "exit" | "echo",
+                _,
+            ) => Ok(()),
+            _ => Err(Self::error(e, Smt2ErrorKind::Unsupported)),
+        }
+    }
+
+    fn declare(&mut self, n: &SExpr, s: &SExpr, script: &mut Smt2Script) -> Result<(), Smt2Error> {
+        let name = Self::symbol(n)?.to_string();
+        let t = Term::new_var(Self::sort(s)?);
+        self.vars.insert(name.clone(), t.clone());
+        script.vars.push((name, t));
+        Ok(())
+    }
+}
+
+/// Reads the declarations and assertions of an SMT-LIB2 script in QF_BV or
+/// QF_ABV. `Bool` is read as `Bv(1)`, `push` and `pop` are not supported.
+pub fn read_smtlib2_str(str: &str) -> Result<Smt2Script, Smt2Error> {
+    let mut script = Smt2Script::default();
+    let mut parser = Smt2Parser::default();
+    for cmd in tokenize(str)? {
+        parser.command(&cmd, &mut script)?;
+    }
+    Ok(script)
+}
+
+pub fn read_smtlib2<R: Read>(mut reader: R) -> Result<Smt2Script, Smt2Error> {
+    let mut str = String::new();
+    reader.read_to_string(&mut str).map_err(|e| Smt2Error {
+        line: 0,
+        kind: Smt2ErrorKind::Io(e),
+    })?;
+    read_smtlib2_str(&str)
+}
+
+pub fn read_smtlib2_file<P: AsRef<Path>>(file: P) -> Result<Smt2Script, Smt2Error> {
+    let file = open_compressed(file).map_err(|e| Smt2Error {
+        line: 0,
+        kind: Smt2ErrorKind::Io(e),
+    })?;
+    read_smtlib2(file)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        DagCnf, Lit,
+        fol::{TEST_LOCK, op::Ult},
+        satif::{CdclSolver, Satif},
+    };
+
+    #[test]
+    fn test_smtlib2_print() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let x = Term::new_var(Sort::Bv(4));
+        let y = Term::new_var(Sort::Bv(4));
+        let (xn, yn) = (Smt2Printer::new().expr(&x), Smt2Printer::new().expr(&y));
+        let s = &x + &y;
+        let p = s.op1(Ult, &x) & s.slice(3, 3);
+        let mut out = Vec::new();
+        write_smtlib2(&[p], &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "(set-logic QF_BV)\n\
+                 (declare-fun {xn} () (_ BitVec 4))\n\
+                 (declare-fun {yn} () (_ BitVec 4))\n\
+                 (assert (= (let ((_s1 (bvadd {xn} {yn}))) \
+                 (bvand (ite (bvult _s1 {xn}) #b1 #b0) ((_ extract 3 3) _s1))) #b1))\n\
+                 (check-sat)\n"
+            )
+        );
+    }
+
+    fn solve(assertions: &[Term]) -> bool {
+        let mut bb = GHashMap::new();
+        let mut cm = GHashMap::new();
+        let mut dc = DagCnf::new();
+        let assumps: Vec<Lit> = assertions
+            .iter()
+            .map(|a| a.bitblast(&mut bb)[0].cnf_encode(&mut dc, &mut cm))
+            .collect();
+        let mut solver = CdclSolver::new();
+        for c in dc.clause() {
+            solver.add_clause(c);
+        }
+        solver.solve(&assumps)
+    }
+
+    #[test]
+    fn test_smtlib2_parse() {
+        let _lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let sat = "(set-logic QF_ABV)
+            (declare-fun x () (_ BitVec 8))
+            (declare-const |y z| (_ BitVec 8))
+            (declare-fun m () (Array (_ BitVec 4) (_ BitVec 8)))
+            (define-fun inc ((a (_ BitVec 8))) (_ BitVec 8) (bvadd a #x01))
+            (define-fun lo () (_ BitVec 4) ((_ extract 3 0) x))
+            (assert (let ((s (inc x)) (t (bvmul x (_ bv3 8))))
+                (and (= t #x0f) (bvult x s) (distinct x |y z|))))
+            (assert (=> true (= ((_ zero_extend 4) lo) (select (store m lo x) lo))))
+            (assert (= ((_ rotate_left 4) x) #x50))
+            ; comment
+            (check-sat)";
+        let unsat = "(declare-fun x () (_ BitVec 8))
+            (assert (bvugt x ((_ sign_extend 4) #b1111)))";
+        let script = read_smtlib2_str(sat).unwrap();
+        assert_eq!(script.vars.len(), 3);
+        assert_eq!(script.vars[1].0, "y z");
+        assert!(solve(&script.assertions));
+        assert!(!solve(&read_smtlib2_str(unsat).unwrap().assertions));
+        let mut out = Vec::new();
+        write_smtlib2(&script.assertions, &mut out).unwrap();
+        let back = read_smtlib2(out.as_slice()).unwrap();
+        assert!(solve(&back.assertions));
+        for bad in [
+            "(assert (bvadd #x1 #x01))",
+            "(declare-fun f ((_ BitVec 1)) Bool)",
+            "(assert (= x #b0))",
+            "(assert true",
+            "(assert (_))",
+        ] {
+            assert!(read_smtlib2_str(bad).is_err());
+        }
+        let err = read_smtlib2_str("(declare-fun x () (_ BitVec 5))\n(assert (= x (_ bv5)))");
+        assert!(matches!(err.unwrap_err().kind, Smt2ErrorKind::Syntax(_)));
+        let err = read_smtlib2_str(
+            "(define-fun f ((a (_ BitVec 4))) (_ BitVec 8) a)\n\
+             (declare-fun x () (_ BitVec 4))\n\
+             (assert (= (f x) #x00))",
+        );
+        assert!(matches!(
+            err.unwrap_err().kind,
+            Smt2ErrorKind::SortMismatch(_)
+        ));
+    }
+}
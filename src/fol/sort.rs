@@ -1,4 +1,4 @@
-use std::fmt::{self, Debug};
+use std::fmt::{self, Debug, Display};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Sort {
@@ -45,3 +45,13 @@ impl Debug for Sort {
         }
     }
 }
+
+/// SMT-LIB notation.
+impl Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Sort::Bv(w) => write!(f, "(_ BitVec {w})"),
+            Sort::Array(i, e) => write!(f, "(Array (_ BitVec {i}) (_ BitVec {e}))"),
+        }
+    }
+}
@@ -1,14 +1,14 @@
 use super::op::{Add, And, Ite, Neg, Not, Or, Sub, Xor};
-use super::{op::DynOp, sort::Sort};
+use super::{BvConst, op::DynOp, sort::Sort};
 use crate::fol::TermVec;
 use crate::fol::op::Slice;
 use giputils::grc::Grc;
 use giputils::hash::GHashMap;
 use lazy_static::lazy_static;
 use std::fmt::{self, Debug};
+use std::hash;
 use std::iter::once;
-use std::ops::{DerefMut, Index};
-use std::{hash, ops};
+use std::ops::Index;
 use std::{hash::Hash, ops::Deref};
 
 #[derive(Clone)]
@@ -19,7 +19,7 @@ pub struct Term {
 impl Term {
     #[inline]
     pub fn bool_const(c: bool) -> Term {
-        tm().new_term(TermType::Const(BvConst::new(&[c])), Sort::Bv(1))
+        Self::bv_const(BvConst::new(&[c]))
     }
 
     #[inline]
@@ -30,39 +30,22 @@ impl Term {
 
     #[inline]
     pub fn bv_const_zero(len: usize) -> Term {
-        tm().new_term(
-            TermType::Const(BvConst::new(&vec![false; len])),
-            Sort::Bv(len),
-        )
+        Self::bv_const(BvConst::zero(len))
     }
 
     #[inline]
     pub fn bv_const_one(len: usize) -> Term {
-        let mut c = vec![false; len];
-        c[0] = true;
-        tm().new_term(TermType::Const(BvConst::new(&c)), Sort::Bv(len))
+        Self::bv_const(BvConst::one(len))
     }
 
     #[inline]
     pub fn bv_const_ones(len: usize) -> Term {
-        tm().new_term(
-            TermType::Const(BvConst::new(&vec![true; len])),
-            Sort::Bv(len),
-        )
+        Self::bv_const(BvConst::ones(len))
     }
 
     #[inline]
-    pub fn bv_const_from_usize(mut v: usize, width: usize) -> Term {
-        let mut bv = Vec::new();
-        while v > 0 {
-            bv.push(width & 1 == 1);
-            v >>= 1;
-        }
-        while bv.len() < width {
-            bv.push(false);
-        }
-        bv.truncate(width);
-        Self::bv_const(BvConst::new(&bv))
+    pub fn bv_const_from_usize(v: usize, width: usize) -> Term {
+        Self::bv_const(BvConst::from_u64(v as u64, width))
     }
 
     #[inline]
@@ -340,81 +323,6 @@ pub enum TermType {
     Op(OpTerm),
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub struct BvConst {
-    c: Vec<bool>,
-}
-
-impl BvConst {
-    #[inline]
-    pub fn new(c: &[bool]) -> Self {
-        Self { c: c.to_vec() }
-    }
-
-    #[inline]
-    pub fn is_zero(&self) -> bool {
-        self.c.iter().all(|x| !x)
-    }
-
-    #[inline]
-    pub fn is_ones(&self) -> bool {
-        self.c.iter().all(|x| *x)
-    }
-
-    #[inline]
-    #[allow(clippy::len_without_is_empty)]
-    pub fn len(&self) -> usize {
-        self.c.len()
-    }
-
-    pub fn bool(&self) -> Option<bool> {
-        if self.c.len() == 1 {
-            Some(self.c[0])
-        } else {
-            None
-        }
-    }
-}
-
-impl Deref for BvConst {
-    type Target = [bool];
-
-    #[inline]
-    fn deref(&self) -> &Self::Target {
-        &self.c
-    }
-}
-
-impl DerefMut for BvConst {
-    #[inline]
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.c
-    }
-}
-
-impl Debug for BvConst {
-    #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let c: String = self
-            .c
-            .iter()
-            .map(|b| if *b { '1' } else { '0' })
-            .rev()
-            .collect();
-        write!(f, "BvConst({c:})")
-    }
-}
-
-impl ops::Not for &BvConst {
-    type Output = BvConst;
-
-    #[inline]
-    fn not(self) -> Self::Output {
-        let c = self.c.iter().map(|b| !b).collect();
-        BvConst { c }
-    }
-}
-
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct OpTerm {
     pub op: DynOp,
@@ -0,0 +1,535 @@
+use crate::{
+    Lit, LitVec, Var,
+    dimacs::{DimacsError, DimacsErrorKind, open_compressed},
+    satif::{Satif, SatifFeatures},
+};
+use std::{
+    io::{self, BufRead, Write},
+    path::Path,
+    time::Duration,
+};
+
+/// Incremental CNF (iCNF) support. As in `dimacs`, solver variable `Var(k)` is
+/// iCNF variable `k`. `Var::CONST` has no iCNF variable and is written as the
+/// constant false, see [`IcnfWriter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcnfCommand {
+    Clause(LitVec),
+    Assume(LitVec),
+}
+
+/// Writes iCNF commands. Clauses satisfied by a `Var::CONST` literal are
+/// skipped and false constant literals are dropped; an assumption on a false
+/// constant can not be written and is an `InvalidInput` error.
+pub struct IcnfWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> IcnfWriter<W> {
+    pub fn new(mut out: W) -> io::Result<Self> {
+        writeln!(out, "p inccnf")?;
+        Ok(Self { out })
+    }
+
+    fn write_lits(&mut self, prefix: &str, lits: &[Lit]) -> io::Result<()> {
+        self.out.write_all(prefix.as_bytes())?;
+        for l in lits.iter().filter(|l| l.var() != Var::CONST) {
+            write!(self.out, "{l} ")?;
+        }
+        writeln!(self.out, "0")
+    }
+
+    #[inline]
+    pub fn add_clause(&mut self, clause: &[Lit]) -> io::Result<()> {
+        if clause.contains(&Lit::constant(true)) {
+            return Ok(());
+        }
+        self.write_lits("", clause)
+    }
+
+    #[inline]
+    pub fn assume(&mut self, assumps: &[Lit]) -> io::Result<()> {
+        if assumps.contains(&Lit::constant(false)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "icnf cannot assume the false constant",
+            ));
+        }
+        self.write_lits("a ", assumps)
+    }
+
+    #[inline]
+    pub fn write_command(&mut self, cmd: &IcnfCommand) -> io::Result<()> {
+        match cmd {
+            IcnfCommand::Clause(cls) => self.add_clause(cls),
+            IcnfCommand::Assume(assumps) => self.assume(assumps),
+        }
+    }
+
+    #[inline]
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+pub struct IcnfReader<R: BufRead> {
+    reader: R,
+    buf: String,
+    line: usize,
+    header: bool,
+    lits: LitVec,
+    assume: Option<usize>,
+    eof: bool,
+}
+
+impl<R: BufRead> IcnfReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+            header: false,
+            lits: LitVec::new(),
+            assume: None,
+            eof: false,
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<Option<IcnfCommand>, DimacsError> {
+        let tokens: Vec<(usize, &str)> = line
+            .split_ascii_whitespace()
+            .map(|t| (t.as_ptr() as usize - line.as_ptr() as usize + 1, t))
+            .collect();
+        let Some(&(col, first)) = tokens.first() else {
+            return Ok(None);
+        };
+        if first.starts_with('c') {
+            return Ok(None);
+        }
+        if first == "p" {
+            let kind = if self.header {
+                DimacsErrorKind::DuplicateHeader
+            } else if !self.lits.is_empty() || self.assume.is_some() {
+                DimacsErrorKind::HeaderAfterClause
+            } else if tokens.len() != 2 || tokens[1].1 != "inccnf" {
+                DimacsErrorKind::InvalidHeader
+            } else {
+                self.header = true;
+                return Ok(None);
+            };
+            return Err(DimacsError::new(self.line, col, kind));
+        }
+        let mut tokens = tokens.as_slice();
+        if first == "a" {
+            if !self.lits.is_empty() || self.assume.is_some() {
+                return Err(DimacsError::new(
+                    self.line,
+                    col,
+                    DimacsErrorKind::UnterminatedClause,
+                ));
+            }
+            self.assume = Some(self.line);
+            tokens = &tokens[1..];
+        }
+        let mut res = None;
+        for &(col, token) in tokens.iter() {
+            if res.is_some() {
+                return Err(DimacsError::new(
+                    self.line,
+                    col,
+                    DimacsErrorKind::InvalidLiteral(token.to_string()),
+                ));
+            }
+            let lit: i64 = token.parse().map_err(|_| {
+                DimacsError::new(
+                    self.line,
+                    col,
+                    DimacsErrorKind::InvalidLiteral(token.to_string()),
+                )
+            })?;
+            if lit == 0 {
+                let lits = std::mem::take(&mut self.lits);
+                res = Some(if self.assume.take().is_some() {
+                    IcnfCommand::Assume(lits)
+                } else {
+                    IcnfCommand::Clause(lits)
+                });
+                continue;
+            }
+            let var = match u32::try_from(lit.unsigned_abs()) {
+                Ok(v) if v < u32::MAX >> 1 => v,
+                _ => {
+                    return Err(DimacsError::new(
+                        self.line,
+                        col,
+                        DimacsErrorKind::InvalidLiteral(token.to_string()),
+                    ));
+                }
+            };
+            self.lits.push(Lit::new(Var(var), lit > 0));
+        }
+        Ok(res)
+    }
+}
+
+impl<R: BufRead> Iterator for IcnfReader<R> {
+    type Item = Result<IcnfCommand, DimacsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.eof {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Err(e) => {
+                    self.eof = true;
+                    return Some(Err(DimacsError::new(
+                        self.line + 1,
+                        0,
+                        DimacsErrorKind::Io(e),
+                    )));
+                }
+                Ok(0) => {
+                    self.eof = true;
+                    if !self.lits.is_empty() || self.assume.is_some() {
+                        return Some(Err(DimacsError::new(
+                            self.line,
+                            0,
+                            DimacsErrorKind::UnterminatedClause,
+                        )));
+                    }
+                }
+                Ok(_) => {
+                    self.line += 1;
+                    let buf = std::mem::take(&mut self.buf);
+                    let res = self.parse_line(&buf);
+                    self.buf = buf;
+                    match res {
+                        Ok(Some(cmd)) => return Some(Ok(cmd)),
+                        Ok(None) => (),
+                        Err(e) => {
+                            self.eof = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Replays an iCNF trace against `solver`, returning the result of every
+/// assumption line in order.
+pub fn replay<S: Satif + ?Sized, R: BufRead>(
+    solver: &mut S,
+    reader: R,
+) -> Result<Vec<bool>, DimacsError> {
+    let mut res = Vec::new();
+    for cmd in IcnfReader::new(reader) {
+        let cmd = cmd?;
+        let (IcnfCommand::Clause(lits) | IcnfCommand::Assume(lits)) = &cmd;
+        if let Some(m) = lits.iter().map(|l| l.var()).max() {
+            solver.new_var_to(m);
+        }
+        match &cmd {
+            IcnfCommand::Clause(cls) => solver.add_clause(cls),
+            IcnfCommand::Assume(assumps) => res.push(solver.solve(assumps)),
+        }
+    }
+    Ok(res)
+}
+
+pub fn replay_file<S: Satif + ?Sized, P: AsRef<Path>>(
+    solver: &mut S,
+    file: P,
+) -> Result<Vec<bool>, DimacsError> {
+    let file = open_compressed(file).map_err(|e| DimacsError::new(0, 0, DimacsErrorKind::Io(e)))?;
+    replay(solver, file)
+}
+
+/// A `Satif` wrapper that records every `add_clause` and `solve` call made
+/// through it as an iCNF trace. Clauses added to `solver` before wrapping are
+/// not part of the trace. Temporary constraints are passed to `solver` and
+/// recorded as clauses guarded by a fresh activation literal. The first io
+/// error, or a limited solve that gives up, stops the recording and is
+/// returned by [`IcnfRecorder::into_inner`], the solver keeps working.
+pub struct IcnfRecorder<S: Satif, W: Write> {
+    solver: S,
+    writer: IcnfWriter<W>,
+    error: Option<io::Error>,
+}
+
+impl<S: Satif, W: Write> IcnfRecorder<S, W> {
+    pub fn new(solver: S, out: W) -> io::Result<Self> {
+        let writer = IcnfWriter::new(out)?;
+        Ok(Self {
+            solver,
+            writer,
+            error: None,
+        })
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.solver
+    }
+
+    /// The io error that stopped the recording, if any.
+    #[inline]
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn into_inner(mut self) -> io::Result<(S, W)> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()?;
+        Ok((self.solver, self.writer.into_inner()))
+    }
+
+    fn record(&mut self, f: impl FnOnce(&mut IcnfWriter<W>) -> io::Result<()>) {
+        if self.error.is_none()
+            && let Err(e) = f(&mut self.writer)
+        {
+            self.error = Some(e);
+        }
+    }
+
+    /// Adds the `constraint` clauses guarded by a fresh activation literal,
+    /// which is never assumed again, and returns `assumps` extended by it.
+    fn activate(&mut self, assumps: &[Lit], constraint: Vec<LitVec>) -> LitVec {
+        let mut assumps = LitVec::from(assumps);
+        if constraint.is_empty() {
+            return assumps;
+        }
+        let act = self.solver.new_var().lit();
+        for mut c in constraint {
+            c.push(!act);
+            self.add_clause(&c);
+        }
+        assumps.push(act);
+        assumps
+    }
+}
+
+impl<S: Satif, W: Write> Satif for IcnfRecorder<S, W> {
+    #[inline]
+    fn new_var(&mut self) -> Var {
+        self.solver.new_var()
+    }
+
+    #[inline]
+    fn num_var(&self) -> usize {
+        self.solver.num_var()
+    }
+
+    #[inline]
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.record(|w| w.add_clause(clause));
+        self.solver.add_clause(clause);
+    }
+
+    #[inline]
+    fn solve(&mut self, assumps: &[Lit]) -> bool {
+        self.record(|w| {
+            w.assume(assumps)?;
+            w.flush()
+        });
+        self.solver.solve(assumps)
+    }
+
+    fn solve_with_constraint(&mut self, assumps: &[Lit], constraint: Vec<LitVec>) -> bool {
+        let assumps = self.activate(assumps, constraint);
+        self.solve(&assumps)
+    }
+
+    fn solve_with_limit(
+        &mut self,
+        assumps: &[Lit],
+        constraint: Vec<LitVec>,
+        limit: Duration,
+    ) -> Option<bool> {
+        let assumps = self.activate(assumps, constraint);
+        self.record(|w| {
+            w.assume(&assumps)?;
+            w.flush()
+        });
+        let res = self.solver.solve_with_limit(&assumps, vec![], limit);
+        if res.is_none() {
+            self.record(|_| {
+                Err(io::Error::other(
+                    "icnf can not express a solve that hit its limit",
+                ))
+            });
+        }
+        res
+    }
+
+    #[inline]
+    fn features(&self) -> SatifFeatures {
+        self.solver.features()
+    }
+
+    #[inline]
+    fn sat_value(&self, lit: Lit) -> Option<bool> {
+        self.solver.sat_value(lit)
+    }
+
+    #[inline]
+    fn unsat_has(&self, lit: Lit) -> bool {
+        self.solver.unsat_has(lit)
+    }
+
+    #[inline]
+    fn simplify(&mut self) -> Option<bool> {
+        self.solver.simplify()
+    }
+
+    #[inline]
+    fn set_frozen(&mut self, var: Var, frozen: bool) {
+        self.solver.set_frozen(var, frozen)
+    }
+
+    #[inline]
+    fn clauses(&self) -> Vec<LitVec> {
+        self.solver.clauses()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::satif::CdclSolver;
+
+    #[test]
+    fn test_icnf() {
+        let cmds = vec![
+            IcnfCommand::Clause(LitVec::from([Lit::from(1), Lit::from(-2)])),
+            IcnfCommand::Assume(LitVec::from([Lit::from(2)])),
+            IcnfCommand::Clause(LitVec::from([Lit::from(-1)])),
+            IcnfCommand::Assume(LitVec::new()),
+        ];
+        let mut writer = IcnfWriter::new(Vec::new()).unwrap();
+        for cmd in cmds.iter() {
+            writer.write_command(cmd).unwrap();
+        }
+        writer
+            .add_clause(&[Lit::constant(true), Lit::from(3)])
+            .unwrap();
+        writer
+            .add_clause(&[Lit::constant(false), Lit::from(3)])
+            .unwrap();
+        assert!(writer.assume(&[Lit::constant(false)]).is_err());
+        let out = writer.into_inner();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "p inccnf\n1 -2 0\na 2 0\n-1 0\na 0\n3 0\n"
+        );
+        let read: Vec<_> = IcnfReader::new(out.as_slice())
+            .map(|c| c.unwrap())
+            .collect();
+        assert_eq!(read[..4], cmds);
+        let err = IcnfReader::new("p inccnf\n1 2\na 1 0\n".as_bytes())
+            .find_map(|c| c.err())
+            .unwrap();
+        assert_eq!(err.line, 3);
+        assert!(matches!(err.kind, DimacsErrorKind::UnterminatedClause));
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let mut rec = IcnfRecorder::new(CdclSolver::new(), Vec::new()).unwrap();
+        let (a, b, c) = (
+            rec.new_var().lit(),
+            rec.new_var().lit(),
+            rec.new_var().lit(),
+        );
+        rec.add_clause(&[Lit::constant(true)]);
+        rec.add_clause(&[a, b]);
+        rec.add_clause(&[!a, c]);
+        let mut res = vec![rec.solve(&[!b]), rec.solve(&[!b, !c])];
+        res.push(rec.solve_with_constraint(&[], vec![LitVec::from([!a]), LitVec::from([!b])]));
+        let limit = Duration::from_secs(10);
+        res.push(
+            rec.solve_with_limit(&[!b], vec![LitVec::from([c])], limit)
+                .unwrap(),
+        );
+        rec.add_clause(&[!c]);
+        res.push(rec.solve(&[]));
+        res.push(rec.solve(&[!b]));
+        assert_eq!(res, [true, false, false, true, true, false]);
+        let (_, trace) = rec.into_inner().unwrap();
+        assert_eq!(
+            replay(&mut CdclSolver::new(), trace.as_slice()).unwrap(),
+            res
+        );
+
+        let file = std::env::temp_dir().join(format!(
+            "logicrs_icnf_test_record_replay_{}.icnf.gz",
+            std::process::id()
+        ));
+        let mut out = crate::dimacs::create_compressed(&file).unwrap();
+        out.write_all(&trace).unwrap();
+        out.finish().unwrap();
+        let replayed = replay_file(&mut CdclSolver::new(), &file);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(replayed.unwrap(), res);
+
+        let mut buf = [0; 16];
+        let mut rec = IcnfRecorder::new(CdclSolver::new(), &mut buf[..]).unwrap();
+        let a = rec.new_var().lit();
+        rec.add_clause(&[a]);
+        rec.add_clause(&[!a]);
+        assert!(rec.error().is_some());
+        assert!(!rec.solve(&[]));
+        let err = rec.into_inner().err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    }
+
+    /// Gives up on every limited solve.
+    struct GiveUp(CdclSolver);
+
+    impl Satif for GiveUp {
+        fn new_var(&mut self) -> Var {
+            self.0.new_var()
+        }
+
+        fn num_var(&self) -> usize {
+            self.0.num_var()
+        }
+
+        fn add_clause(&mut self, clause: &[Lit]) {
+            self.0.add_clause(clause)
+        }
+
+        fn solve(&mut self, assumps: &[Lit]) -> bool {
+            self.0.solve(assumps)
+        }
+
+        fn solve_with_limit(&mut self, _: &[Lit], _: Vec<LitVec>, _: Duration) -> Option<bool> {
+            None
+        }
+
+        fn sat_value(&self, lit: Lit) -> Option<bool> {
+            self.0.sat_value(lit)
+        }
+    }
+
+    #[test]
+    fn test_record_limit() {
+        let mut rec = IcnfRecorder::new(GiveUp(CdclSolver::new()), Vec::new()).unwrap();
+        let a = rec.new_var().lit();
+        rec.add_clause(&[a]);
+        assert_eq!(
+            rec.solve_with_limit(&[], vec![], Duration::from_secs(1)),
+            None
+        );
+        assert!(rec.error().is_some());
+        assert!(rec.into_inner().is_err());
+    }
+}
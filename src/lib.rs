@@ -1,18 +1,24 @@
 #![feature(step_trait, try_trait_v2)]
 
+pub mod aiger;
 mod assign;
 mod cnf;
+pub mod count;
 mod cstdagcnf;
 mod dagcnf;
 pub mod dimacs;
 pub mod fol;
+pub mod icnf;
 mod lbool;
 mod litordvec;
 mod litvec;
 mod litvvec;
 mod occur;
+pub mod opb;
+pub mod proof;
 pub mod satif;
 mod utils;
+pub mod wcnf;
 
 pub use assign::*;
 pub use cnf::*;
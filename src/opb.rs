@@ -0,0 +1,360 @@
+use crate::{
+    Cnf, DagCnf, Lit, LitVec, PbEncoding, Var, VarAssign,
+    dimacs::{DimacsError, DimacsErrorKind, open_compressed},
+};
+use std::{io::BufRead, path::Path};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PbOp {
+    Ge,
+    Le,
+    Eq,
+}
+
+/// `sum(a * l) op rhs` with signed coefficients. For `<=` and `=` the
+/// coefficients and `rhs` are negated and must not be `i64::MIN`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PbConstraint {
+    pub terms: Vec<(Lit, i64)>,
+    pub op: PbOp,
+    pub rhs: i64,
+}
+
+/// `sum(w * l) >= k` with positive weights `w`.
+pub type PbGe = (Vec<(Lit, u64)>, u64);
+
+impl PbConstraint {
+    /// Rewrites `sum(a * l) >= rhs` with positive coefficients, `Ok(None)`
+    /// when it holds trivially and `Err` when the bound exceeds `u64`.
+    fn normalize_ge(terms: &[(Lit, i64)], rhs: i64) -> Result<Option<PbGe>, ()> {
+        let mut k = rhs as i128;
+        let mut total = 0_i128;
+        let mut res = Vec::with_capacity(terms.len());
+        for &(l, a) in terms {
+            if a < 0 {
+                k -= a as i128;
+                res.push((!l, a.unsigned_abs()));
+            } else if a > 0 {
+                res.push((l, a as u64));
+            }
+            total += a.unsigned_abs() as i128;
+        }
+        if k <= 0 {
+            return Ok(None);
+        }
+        if k > total {
+            return Ok(Some((Vec::new(), 1)));
+        }
+        Ok(Some((res, u64::try_from(k).map_err(|_| ())?)))
+    }
+
+    /// The constraint as a conjunction of `>=` constraints with positive
+    /// coefficients, `None` if a normalized bound does not fit in `u64`.
+    pub fn normalize(&self) -> Option<Vec<PbGe>> {
+        let mut res = Vec::new();
+        if matches!(self.op, PbOp::Ge | PbOp::Eq) {
+            res.extend(Self::normalize_ge(&self.terms, self.rhs).ok()?);
+        }
+        if matches!(self.op, PbOp::Le | PbOp::Eq) {
+            let neg = |a: i64| {
+                a.checked_neg()
+                    .expect("pb constraint can not negate i64::MIN")
+            };
+            let terms: Vec<(Lit, i64)> = self.terms.iter().map(|(l, a)| (*l, neg(*a))).collect();
+            res.extend(Self::normalize_ge(&terms, neg(self.rhs)).ok()?);
+        }
+        Some(res)
+    }
+}
+
+/// A pseudo-Boolean problem in OPB format. OPB variable `xk` is `Var(k)`.
+#[derive(Debug, Clone, Default)]
+pub struct Opb {
+    pub max_var: Var,
+    /// Linear objective to minimize.
+    pub objective: Option<Vec<(Lit, i64)>>,
+    pub constraints: Vec<PbConstraint>,
+}
+
+impl Opb {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_constraint(&mut self, c: PbConstraint) {
+        if let Some(m) = c.terms.iter().map(|(l, _)| l.var()).max() {
+            self.max_var = self.max_var.max(m);
+        }
+        self.constraints.push(c);
+    }
+
+    /// Encodes every constraint into `DagCnf` and returns it with the
+    /// literals that must hold for the constraints to be satisfied. Panics if
+    /// a constraint can not be normalized, which [`read_opb`] rejects.
+    pub fn to_dagcnf(&self, enc: PbEncoding) -> (DagCnf, LitVec) {
+        let mut dc = DagCnf::new();
+        dc.new_var_to(self.max_var);
+        let mut assert = LitVec::new();
+        for c in self.constraints.iter() {
+            let normalized = c.normalize().expect("pb constraint bound exceeds u64");
+            for (terms, k) in normalized {
+                let o = dc.new_pb_at_least(&terms, k, enc);
+                if !o.is_constant(true) {
+                    assert.push(o);
+                }
+            }
+        }
+        (dc, assert)
+    }
+
+    pub fn to_cnf(&self, enc: PbEncoding) -> Cnf {
+        let (dc, assert) = self.to_dagcnf(enc);
+        let mut cnf = dc.lower();
+        for l in assert {
+            cnf.add_clause(&[l]);
+        }
+        cnf
+    }
+
+    /// Value of the objective under `model`, 0 if there is none.
+    pub fn objective_value(&self, model: &VarAssign) -> i64 {
+        self.objective
+            .iter()
+            .flatten()
+            .filter(|(l, _)| model.v(*l).is_true())
+            .map(|(_, a)| a)
+            .sum()
+    }
+}
+
+struct OpbParser {
+    line: usize,
+    tokens: Vec<(usize, usize, String)>,
+}
+
+impl OpbParser {
+    fn error(&self, pos: (usize, usize), kind: DimacsErrorKind) -> DimacsError {
+        DimacsError::new(pos.0, pos.1, kind)
+    }
+
+    fn parse_lit(&self, pos: (usize, usize), t: &str) -> Result<Lit, DimacsError> {
+        let (neg, v) = match t.strip_prefix('~') {
+            Some(v) => (true, v),
+            None => (false, t),
+        };
+        v.strip_prefix('x')
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|v| *v > 0 && *v < u32::MAX >> 1)
+            .map(|v| Lit::new(Var(v), !neg))
+            .ok_or_else(|| self.error(pos, DimacsErrorKind::InvalidLiteral(t.to_string())))
+    }
+
+    fn parse_int(&self, pos: (usize, usize), t: &str) -> Result<i64, DimacsError> {
+        t.strip_prefix('+')
+            .unwrap_or(t)
+            .parse()
+            .map_err(|_| self.error(pos, DimacsErrorKind::InvalidWeight(t.to_string())))
+    }
+
+    fn parse_terms(
+        &self,
+        tokens: &[(usize, usize, String)],
+    ) -> Result<Vec<(Lit, i64)>, DimacsError> {
+        if !tokens.len().is_multiple_of(2) {
+            let (l, c, t) = &tokens[tokens.len() - 1];
+            return Err(self.error((*l, *c), DimacsErrorKind::InvalidConstraint(t.clone())));
+        }
+        tokens
+            .chunks(2)
+            .map(|t| {
+                let a = self.parse_int((t[0].0, t[0].1), &t[0].2)?;
+                let l = self.parse_lit((t[1].0, t[1].1), &t[1].2)?;
+                Ok((l, a))
+            })
+            .collect()
+    }
+
+    fn parse_statement(&mut self, opb: &mut Opb) -> Result<(), DimacsError> {
+        let tokens = std::mem::take(&mut self.tokens);
+        let Some((line, col, first)) = tokens.first() else {
+            return Ok(());
+        };
+        if first == "min:" {
+            let obj = self.parse_terms(&tokens[1..])?;
+            if let Some(m) = obj.iter().map(|(l, _)| l.var()).max() {
+                opb.max_var = opb.max_var.max(m);
+            }
+            opb.objective = Some(obj);
+            return Ok(());
+        }
+        let Some(i) = tokens
+            .iter()
+            .position(|(_, _, t)| matches!(t.as_str(), ">=" | "<=" | "="))
+        else {
+            return Err(self.error(
+                (*line, *col),
+                DimacsErrorKind::InvalidConstraint(first.clone()),
+            ));
+        };
+        let op = match tokens[i].2.as_str() {
+            ">=" => PbOp::Ge,
+            "<=" => PbOp::Le,
+            _ => PbOp::Eq,
+        };
+        let terms = self.parse_terms(&tokens[..i])?;
+        let [(l, c, rhs)] = &tokens[i + 1..] else {
+            let (l, c, t) = &tokens[i];
+            return Err(self.error((*l, *c), DimacsErrorKind::InvalidConstraint(t.clone())));
+        };
+        let rhs = self.parse_int((*l, *c), rhs)?;
+        if op != PbOp::Ge {
+            let coefs = terms
+                .iter()
+                .enumerate()
+                .map(|(j, (_, a))| (&tokens[2 * j], *a));
+            for ((l, c, t), a) in coefs.chain([(&tokens[i + 1], rhs)]) {
+                if a.checked_neg().is_none() {
+                    return Err(self.error((*l, *c), DimacsErrorKind::InvalidConstraint(t.clone())));
+                }
+            }
+        }
+        let c = PbConstraint { terms, op, rhs };
+        if c.normalize().is_none() {
+            let (l, c, t) = &tokens[i + 1];
+            return Err(self.error((*l, *c), DimacsErrorKind::InvalidConstraint(t.clone())));
+        }
+        opb.add_constraint(c);
+        Ok(())
+    }
+
+    fn parse_line(&mut self, line: &str, opb: &mut Opb) -> Result<(), DimacsError> {
+        if line.starts_with('*') {
+            return Ok(());
+        }
+        for t in line.split_ascii_whitespace() {
+            let mut col = t.as_ptr() as usize - line.as_ptr() as usize + 1;
+            for (i, part) in t.split(';').enumerate() {
+                if i > 0 {
+                    self.parse_statement(opb)?;
+                    col += 1;
+                }
+                if !part.is_empty() {
+                    self.tokens.push((self.line, col, part.to_string()));
+                }
+                col += part.len();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads a linear OPB instance. Comments start with `*`, an optional
+/// `min:` objective may precede the constraints.
+pub fn read_opb<R: BufRead>(mut reader: R) -> Result<Opb, DimacsError> {
+    let mut opb = Opb::new();
+    let mut parser = OpbParser {
+        line: 0,
+        tokens: Vec::new(),
+    };
+    let mut buf = String::new();
+    loop {
+        buf.clear();
+        let n = reader
+            .read_line(&mut buf)
+            .map_err(|e| DimacsError::new(parser.line + 1, 0, DimacsErrorKind::Io(e)))?;
+        if n == 0 {
+            break;
+        }
+        parser.line += 1;
+        parser.parse_line(&buf, &mut opb)?;
+    }
+    if let Some((line, col, t)) = parser.tokens.last() {
+        return Err(DimacsError::new(
+            *line,
+            *col,
+            DimacsErrorKind::InvalidConstraint(t.clone()),
+        ));
+    }
+    Ok(opb)
+}
+
+pub fn read_opb_str(str: &str) -> Result<Opb, DimacsError> {
+    read_opb(str.as_bytes())
+}
+
+pub fn read_opb_file<P: AsRef<Path>>(file: P) -> Result<Opb, DimacsError> {
+    let file = open_compressed(file).map_err(|e| DimacsError::new(0, 0, DimacsErrorKind::Io(e)))?;
+    read_opb(file)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::count::ExactCounter;
+
+    #[test]
+    fn test_opb() {
+        let opb = read_opb_str(
+            "* #variable= 3 #constraint= 3\nmin: +1 x1 -2 x3 ;\n+3 x1 -2 ~x2\n >= 1;\n+1 x1 +1 x2 +1 x3 = 2 ;\n-1 x3 +1 x1 <= 0 ;\n",
+        )
+        .unwrap();
+        assert_eq!(opb.max_var, Var(3));
+        assert_eq!(opb.constraints.len(), 3);
+        assert_eq!(opb.constraints[0].terms[1], (Lit::from(-2), -2));
+        for enc in [PbEncoding::Adder, PbEncoding::Bdd] {
+            let cnf = opb.to_cnf(enc);
+            let proj = [Var(1), Var(2), Var(3)];
+            assert_eq!(
+                ExactCounter::new(&cnf).with_projection(&proj).count(),
+                Some(1)
+            );
+        }
+        assert!(read_opb_str("+1 x1 >= 1\n").is_err());
+        assert!(read_opb_str("+1 y1 >= 1 ;\n").is_err());
+        assert!(read_opb_str("+1 x1 1 ;\n").is_err());
+        let err = read_opb_str("+1 x1 >= 1;+1 y2 >= 1 ;\n").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 15));
+        assert!(matches!(err.kind, DimacsErrorKind::InvalidLiteral(_)));
+        let min = i64::MIN;
+        assert_eq!(
+            read_opb_str(&format!("{min} x1 >= {min};"))
+                .unwrap()
+                .constraints[0]
+                .rhs,
+            min
+        );
+        for (s, col) in [
+            (format!("+1 x1 {min} x2 <= 0 ;"), 7),
+            (format!("+1 x1 = {min};"), 9),
+        ] {
+            let err = read_opb_str(&s).unwrap_err();
+            assert_eq!((err.line, err.column), (1, col));
+            assert!(
+                matches!(err.kind, DimacsErrorKind::InvalidConstraint(t) if t == min.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_opb_large_coefficients() {
+        let m = i64::MAX;
+        let proj = [Var(1), Var(2), Var(3)];
+        for (s, count) in [
+            (format!("+{m} x1 +{m} x2 -{m} x3 >= {m} ;"), 4),
+            (format!("-{m} x1 -{m} x2 -{m} x3 >= {m} ;"), 0),
+            (format!("+{m} x1 +{m} x2 +{m} x3 <= -1 ;"), 0),
+        ] {
+            let opb = read_opb_str(&s).unwrap();
+            for enc in [PbEncoding::Adder, PbEncoding::Bdd] {
+                let cnf = opb.to_cnf(enc);
+                let n = ExactCounter::new(&cnf).with_projection(&proj).count();
+                assert_eq!(n, Some(count), "{s}");
+            }
+        }
+        let s = format!("+{m} x1 +{m} x2 +{m} x3 +{m} x4 -{m} x5 -{m} x6 -{m} x7 >= 1 ;");
+        let err = read_opb_str(&s).unwrap_err();
+        assert_eq!((err.line, err.column), (1, s.find(">=").unwrap() + 4));
+        assert!(matches!(err.kind, DimacsErrorKind::InvalidConstraint(t) if t == "1"));
+    }
+}
@@ -0,0 +1,560 @@
+use super::ProofStep;
+use crate::{Cnf, Lit, LitMap, LitVec, Var, VarAssign, VarMap};
+use giputils::hash::GHashMap;
+use log::debug;
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    mem::take,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DratError {
+    /// The proof does not lead to the empty clause.
+    NoConflict,
+    /// The lemma added at proof step `step` is neither RUP nor RAT.
+    Unverified { step: usize, lemma: LitVec },
+}
+
+impl Display for DratError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DratError::NoConflict => write!(f, "proof does not derive the empty clause"),
+            DratError::Unverified { step, lemma } => {
+                write!(
+                    f,
+                    "lemma {lemma} at proof step {step} is neither RUP nor RAT"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DratError {}
+
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    Add(usize),
+    Delete(usize),
+    Ignore,
+}
+
+/// Backward DRAT checker in the style of drat-trim. Only lemmas that
+/// contribute to the final conflict are verified; verified lemmas keep their
+/// unit propagation hints so that an LRAT proof can be emitted afterwards.
+/// `Var::CONST` is the constant false, assigned before any clause, so the
+/// constant clause of a `Cnf` is implied and gets no clause id.
+pub struct DratChecker {
+    clauses: Vec<LitVec>,
+    pivot: Vec<Option<Lit>>,
+    num_orig: usize,
+    steps: Vec<Step>,
+    active: Vec<bool>,
+    core: Vec<bool>,
+    hints: Vec<Vec<i64>>,
+    lookup: GHashMap<LitVec, Vec<usize>>,
+    watches: LitMap<Vec<usize>>,
+    units: Vec<usize>,
+    value: VarAssign,
+    reason: VarMap<Option<usize>>,
+    seen: VarMap<bool>,
+    trail: Vec<Lit>,
+    max_var: Var,
+    target: Option<usize>,
+}
+
+impl DratChecker {
+    pub fn new(cnf: &Cnf) -> Self {
+        let mut res = Self {
+            clauses: Vec::new(),
+            pivot: Vec::new(),
+            num_orig: 0,
+            steps: Vec::new(),
+            active: Vec::new(),
+            core: Vec::new(),
+            hints: Vec::new(),
+            lookup: GHashMap::new(),
+            watches: LitMap::new(),
+            units: Vec::new(),
+            value: VarAssign::new(),
+            reason: VarMap::new(),
+            seen: VarMap::new(),
+            trail: Vec::new(),
+            max_var: Var::CONST,
+            target: None,
+        };
+        res.reserve(cnf.max_var());
+        res.trail.push(Lit::constant(true));
+        let constant = LitVec::from(Lit::constant(true));
+        for cls in cnf.iter().filter(|c| **c != constant) {
+            res.new_clause(cls);
+        }
+        res.num_orig = res.clauses.len();
+        res
+    }
+
+    fn reserve(&mut self, var: Var) {
+        if var > self.max_var {
+            self.max_var = var;
+        }
+        self.watches.reserve(self.max_var);
+        self.value.reserve(self.max_var);
+        self.reason.reserve(self.max_var);
+        self.seen.reserve(self.max_var);
+    }
+
+    /// The LRAT id of clause `cid`, ids start from 1.
+    #[inline]
+    fn id(&self, cid: usize) -> i64 {
+        cid as i64 + 1
+    }
+
+    fn new_clause(&mut self, lits: &[Lit]) -> usize {
+        let cid = self.clauses.len();
+        let mut key = LitVec::from(lits);
+        key.sort();
+        key.dedup();
+        if let Some(m) = key.iter().map(|l| l.var()).max() {
+            self.reserve(m);
+        }
+        self.pivot.push(lits.first().copied());
+        let cls = key.clone();
+        match cls.len() {
+            0 | 1 => self.units.push(cid),
+            _ => {
+                self.watches[!cls[0]].push(cid);
+                self.watches[!cls[1]].push(cid);
+            }
+        }
+        self.clauses.push(cls);
+        self.active.push(true);
+        self.core.push(false);
+        self.hints.push(Vec::new());
+        self.lookup.entry(key).or_default().push(cid);
+        cid
+    }
+
+    fn delete_clause(&mut self, lits: &[Lit]) -> Option<usize> {
+        let mut key = LitVec::from(lits);
+        key.sort();
+        key.dedup();
+        if key.len() == 1 {
+            debug!("drat: ignore deletion of unit clause {key}");
+            return None;
+        }
+        let cid = self.lookup.get_mut(&key).and_then(|c| c.pop());
+        match cid {
+            Some(cid) => self.active[cid] = false,
+            None => debug!("drat: ignore deletion of missing clause {key}"),
+        }
+        cid
+    }
+
+    /// Unassigns everything but the constant at the bottom of the trail.
+    fn reset(&mut self) {
+        for l in self.trail.split_off(1) {
+            self.value.set_none(l.var());
+            self.reason[l.var()] = None;
+        }
+    }
+
+    #[inline]
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        self.value.set(lit);
+        self.reason[lit.var()] = reason;
+        self.trail.push(lit);
+    }
+
+    fn propagate(&mut self, mut qhead: usize) -> Option<usize> {
+        while qhead < self.trail.len() {
+            let p = self.trail[qhead];
+            qhead += 1;
+            let false_lit = !p;
+            let mut ws = take(&mut self.watches[p]);
+            let (mut i, mut j) = (0, 0);
+            let mut conflict = None;
+            'w: while i < ws.len() {
+                let cid = ws[i];
+                i += 1;
+                if !self.active[cid] {
+                    ws[j] = cid;
+                    j += 1;
+                    continue;
+                }
+                let c = &mut self.clauses[cid];
+                if c[0] == false_lit {
+                    c.swap(0, 1);
+                }
+                if self.value.v(c[0]).is_true() {
+                    ws[j] = cid;
+                    j += 1;
+                    continue;
+                }
+                for k in 2..c.len() {
+                    if !self.value.v(c[k]).is_false() {
+                        c.swap(1, k);
+                        let nl = !c[1];
+                        self.watches[nl].push(cid);
+                        continue 'w;
+                    }
+                }
+                ws[j] = cid;
+                j += 1;
+                let first = c[0];
+                if self.value.v(first).is_false() {
+                    conflict = Some(cid);
+                    while i < ws.len() {
+                        ws[j] = ws[i];
+                        i += 1;
+                        j += 1;
+                    }
+                } else {
+                    self.enqueue(first, Some(cid));
+                }
+            }
+            ws.truncate(j);
+            self.watches[p] = ws;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Collects the clauses involved in deriving the conflict on `clause`, or
+    /// on the literal `lit` being both assumed and implied, in trail order.
+    fn analyze(&mut self, clause: Option<usize>, lit: Option<Lit>) -> Vec<usize> {
+        let mut used = Vec::new();
+        if let Some(c) = clause {
+            for l in self.clauses[c].iter() {
+                self.seen[l.var()] = true;
+            }
+        }
+        if let Some(l) = lit {
+            self.seen[l.var()] = true;
+        }
+        for i in (0..self.trail.len()).rev() {
+            let v = self.trail[i].var();
+            if !self.seen[v] {
+                continue;
+            }
+            self.seen[v] = false;
+            if let Some(r) = self.reason[v] {
+                used.push(r);
+                for l in self.clauses[r].iter() {
+                    if l.var() != v {
+                        self.seen[l.var()] = true;
+                    }
+                }
+            }
+        }
+        self.seen[Var::CONST] = false;
+        used.reverse();
+        used.extend(clause);
+        used
+    }
+
+    /// Checks whether `lits` is a reverse unit propagation consequence of the
+    /// active clauses, returning the clauses used in trail order.
+    fn rup(&mut self, lits: &[Lit]) -> Option<Vec<usize>> {
+        self.reset();
+        for &l in lits.iter() {
+            match self.value.v(!l) {
+                v if v.is_true() => continue,
+                v if v.is_false() => {
+                    let res = self.analyze(None, Some(l));
+                    self.reset();
+                    return Some(res);
+                }
+                _ => self.enqueue(!l, None),
+            }
+        }
+        let mut conflict = None;
+        for i in 0..self.units.len() {
+            let cid = self.units[i];
+            if !self.active[cid] {
+                continue;
+            }
+            let Some(&u) = self.clauses[cid].first() else {
+                conflict = Some(cid);
+                break;
+            };
+            let v = self.value.v(u);
+            if v.is_false() {
+                conflict = Some(cid);
+                break;
+            } else if v.is_none() {
+                self.enqueue(u, Some(cid));
+            }
+        }
+        if conflict.is_none() {
+            conflict = self.propagate(0);
+        }
+        let res = conflict.map(|c| self.analyze(Some(c), None));
+        self.reset();
+        res
+    }
+
+    fn verify(&mut self, cid: usize) -> bool {
+        let lemma = self.clauses[cid].clone();
+        if let Some(used) = self.rup(&lemma) {
+            self.hints[cid] = used.iter().map(|&u| self.id(u)).collect();
+            for u in used {
+                self.core[u] = true;
+            }
+            return true;
+        }
+        let Some(pivot) = self.pivot[cid] else {
+            return false;
+        };
+        let mut hints = Vec::new();
+        let mut used_all = Vec::new();
+        for d in 0..self.clauses.len() {
+            if !self.active[d] || !self.clauses[d].contains(&!pivot) {
+                continue;
+            }
+            let mut resolvent = lemma.clone();
+            resolvent.extend(self.clauses[d].iter().filter(|l| **l != !pivot));
+            let Some(used) = self.rup(&resolvent) else {
+                return false;
+            };
+            hints.push(-self.id(d));
+            hints.extend(used.iter().map(|&u| self.id(u)));
+            used_all.push(d);
+            used_all.extend(used);
+        }
+        self.hints[cid] = hints;
+        for u in used_all {
+            self.core[u] = true;
+        }
+        true
+    }
+
+    /// Checks `proof` against the formula. On success the proof can be
+    /// written as LRAT with [`DratChecker::write_lrat`].
+    pub fn check(&mut self, proof: impl IntoIterator<Item = ProofStep>) -> Result<(), DratError> {
+        for step in proof {
+            let step = match step {
+                ProofStep::Add(lits) => {
+                    let cid = self.new_clause(&lits);
+                    self.steps.push(Step::Add(cid));
+                    if lits.is_empty() {
+                        break;
+                    }
+                    continue;
+                }
+                ProofStep::Delete(lits) => match self.delete_clause(&lits) {
+                    Some(cid) => Step::Delete(cid),
+                    None => Step::Ignore,
+                },
+            };
+            self.steps.push(step);
+        }
+        let target = match self.steps.last() {
+            Some(&Step::Add(cid)) if self.clauses[cid].is_empty() => {
+                self.steps.pop();
+                cid
+            }
+            _ => self.new_clause(&[]),
+        };
+        self.active[target] = false;
+        let Some(used) = self.rup(&[]) else {
+            return Err(DratError::NoConflict);
+        };
+        self.hints[target] = used.iter().map(|&u| self.id(u)).collect();
+        self.core[target] = true;
+        for u in used {
+            self.core[u] = true;
+        }
+        self.target = Some(target);
+        for i in (0..self.steps.len()).rev() {
+            match self.steps[i] {
+                Step::Add(cid) => {
+                    self.active[cid] = false;
+                    if self.core[cid] && !self.verify(cid) {
+                        return Err(DratError::Unverified {
+                            step: i,
+                            lemma: LitVec::from(&self.clauses[cid][..]),
+                        });
+                    }
+                }
+                Step::Delete(cid) => self.active[cid] = true,
+                Step::Ignore => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of proof lemmas needed for the final conflict.
+    pub fn num_core_lemma(&self) -> usize {
+        self.core[self.num_orig..].iter().filter(|c| **c).count()
+    }
+
+    fn write_lemma(&self, out: &mut impl Write, cid: usize) -> io::Result<()> {
+        write!(out, "{} ", self.id(cid))?;
+        let mut lits = self.clauses[cid].clone();
+        if let Some(p) = self.pivot[cid]
+            && let Some(i) = lits.iter().position(|l| *l == p)
+        {
+            lits.swap(0, i);
+        }
+        for l in lits.iter() {
+            write!(out, "{l} ")?;
+        }
+        write!(out, "0 ")?;
+        for h in self.hints[cid].iter() {
+            write!(out, "{h} ")?;
+        }
+        writeln!(out, "0")
+    }
+
+    /// Writes the verified proof as LRAT. The clauses of the `Cnf` other than
+    /// its constant clause are numbered from 1 in order, so for a `Cnf` read by
+    /// `DimacsReader` the ids match the DIMACS clause order.
+    pub fn write_lrat(&self, mut out: impl Write) -> io::Result<()> {
+        let target = self.target.expect("write lrat before a successful check");
+        let mut last = self.num_orig as i64;
+        for step in self.steps.iter() {
+            match *step {
+                Step::Add(cid) if self.core[cid] => {
+                    self.write_lemma(&mut out, cid)?;
+                    last = self.id(cid);
+                }
+                Step::Delete(cid) if self.core[cid] => {
+                    writeln!(out, "{last} d {} 0", self.id(cid))?
+                }
+                _ => (),
+            }
+        }
+        self.write_lemma(&mut out, target)
+    }
+}
+
+pub fn check_drat(cnf: &Cnf, proof: impl IntoIterator<Item = ProofStep>) -> Result<(), DratError> {
+    DratChecker::new(cnf).check(proof)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        dimacs::read_dimacs_str,
+        proof::{DratReader, DratWriter, ProofSink},
+        satif::{CdclSolver, Satif},
+    };
+    use std::{cell::RefCell, rc::Rc};
+
+    fn pigeonhole(n: u32) -> Cnf {
+        let p = |i: u32, j: u32| Var(i * n + j + 1).lit();
+        let mut cnf = Cnf::new();
+        for i in 0..=n {
+            cnf.add_clause(&(0..n).map(|j| p(i, j)).collect::<LitVec>());
+        }
+        for j in 0..n {
+            for i in 0..=n {
+                for k in i + 1..=n {
+                    cnf.add_clause(&[!p(i, j), !p(k, j)]);
+                }
+            }
+        }
+        cnf
+    }
+
+    #[test]
+    fn test_drat() {
+        let cnf = pigeonhole(4);
+        let proof = Rc::new(RefCell::new(Vec::<ProofStep>::new()));
+        let mut solver = CdclSolver::new();
+        solver.set_proof(proof.clone());
+        for cls in cnf.iter() {
+            solver.add_clause(cls);
+        }
+        assert!(!solver.solve(&[]));
+        drop(solver);
+        let proof = proof.take();
+        let proof = &proof;
+        for binary in [false, true] {
+            let mut writer = DratWriter::new(Vec::new(), binary);
+            for step in proof.iter() {
+                match step {
+                    ProofStep::Add(l) => writer.add(l),
+                    ProofStep::Delete(l) => writer.delete(l),
+                }
+            }
+            let out = writer.into_inner().unwrap();
+            let read: Vec<_> = DratReader::new(out.as_slice())
+                .map(|s| s.unwrap())
+                .collect();
+            assert_eq!(&read, proof);
+        }
+        // Deleting 16 and 5 encodes as the whitespace bytes 0x20 and 0x0a.
+        let tricky = vec![
+            ProofStep::Delete(LitVec::from([Lit::from(16)])),
+            ProofStep::Delete(LitVec::from([Lit::from(5), Lit::from(-1)])),
+            ProofStep::Add(LitVec::new()),
+        ];
+        let mut writer = DratWriter::new(Vec::new(), true);
+        for step in tricky.iter() {
+            match step {
+                ProofStep::Add(l) => writer.add(l),
+                ProofStep::Delete(l) => writer.delete(l),
+            }
+        }
+        let out = writer.into_inner().unwrap();
+        assert_eq!(out[..2], [b'd', 0x20]);
+        let read: Vec<_> = DratReader::new(out.as_slice())
+            .map(|s| s.unwrap())
+            .collect();
+        assert_eq!(read, tricky);
+        let mut buf = [0; 4];
+        let mut writer = DratWriter::new(&mut buf[..], false);
+        writer.add(&[Lit::from(1), Lit::from(2)]);
+        writer.add(&[]);
+        assert!(writer.error().is_some());
+        assert_eq!(
+            writer.into_inner().err().unwrap().kind(),
+            std::io::ErrorKind::WriteZero
+        );
+        let mut checker = DratChecker::new(&cnf);
+        checker.check(proof.iter().cloned()).unwrap();
+        let mut lrat = Vec::new();
+        checker.write_lrat(&mut lrat).unwrap();
+        let lrat = String::from_utf8(lrat).unwrap();
+        let last: Vec<&str> = lrat.lines().last().unwrap().split(' ').collect();
+        assert_eq!((last[1], last.last()), ("0", Some(&"0")));
+        assert!(checker.num_core_lemma() > 0);
+    }
+
+    #[test]
+    fn test_lrat() {
+        let lrat = |cnf: &Cnf, proof: Vec<ProofStep>| {
+            let mut checker = DratChecker::new(cnf);
+            checker.check(proof)?;
+            let mut lrat = Vec::new();
+            checker.write_lrat(&mut lrat).unwrap();
+            Ok::<_, DratError>(String::from_utf8(lrat).unwrap())
+        };
+        let add = |l: &[i32]| ProofStep::Add(l.iter().map(|l| Lit::from(*l)).collect());
+        let cnf = read_dimacs_str("p cnf 2 4\n1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n").unwrap();
+        assert_eq!(
+            lrat(&cnf, vec![add(&[2]), add(&[])]).unwrap(),
+            "5 2 0 1 2 0\n6 0 5 3 4 0\n"
+        );
+
+        let mut cnf = Cnf::new();
+        cnf.add_clause(&[Lit::constant(false), Lit::from(1)]);
+        cnf.add_clause(&[Lit::from(-1), Lit::from(2)]);
+        cnf.add_clause(&[Lit::from(-1), Lit::from(-2)]);
+        assert_eq!(lrat(&cnf, vec![]).unwrap(), "4 0 1 2 3 0\n");
+
+        let cnf = read_dimacs_str("p cnf 2 3\n1 2 0\n-1 2 0\n1 -2 0\n").unwrap();
+        assert_eq!(lrat(&cnf, vec![]), Err(DratError::NoConflict));
+        assert_eq!(
+            lrat(&cnf, vec![add(&[-2]), add(&[])]),
+            Err(DratError::Unverified {
+                step: 0,
+                lemma: LitVec::from([Lit::from(-2)]),
+            })
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use super::{ProofSink, ProofStep};
+use crate::{
+    Lit, LitVec, Var,
+    dimacs::{DimacsError, DimacsErrorKind},
+};
+use std::io::{self, BufRead, Write};
+
+/// Writes a proof in textual or binary DRAT format. The first io error stops
+/// the writing and is returned by [`DratWriter::into_inner`].
+pub struct DratWriter<W: Write> {
+    out: W,
+    binary: bool,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> DratWriter<W> {
+    #[inline]
+    pub fn new(out: W, binary: bool) -> Self {
+        Self {
+            out,
+            binary,
+            error: None,
+        }
+    }
+
+    /// The io error that stopped the writing, if any.
+    #[inline]
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn record(&mut self, f: impl FnOnce(&mut Self) -> io::Result<()>) {
+        if self.error.is_none()
+            && let Err(e) = f(self)
+        {
+            self.error = Some(e);
+        }
+    }
+
+    fn write(&mut self, delete: bool, lits: &[Lit]) -> io::Result<()> {
+        if self.binary {
+            self.out.write_all(if delete { b"d" } else { b"a" })?;
+            for l in lits.iter() {
+                let mut u = 2 * *l.var() + !l.polarity() as u32;
+                while u > 0x7f {
+                    self.out.write_all(&[(u & 0x7f) as u8 | 0x80])?;
+                    u >>= 7;
+                }
+                self.out.write_all(&[u as u8])?;
+            }
+            self.out.write_all(&[0])
+        } else {
+            if delete {
+                self.out.write_all(b"d ")?;
+            }
+            for l in lits.iter() {
+                write!(self.out, "{l} ")?;
+            }
+            writeln!(self.out, "0")
+        }
+    }
+}
+
+impl<W: Write> ProofSink for DratWriter<W> {
+    #[inline]
+    fn add(&mut self, lits: &[Lit]) {
+        self.record(|w| w.write(false, lits));
+    }
+
+    #[inline]
+    fn delete(&mut self, lits: &[Lit]) {
+        self.record(|w| w.write(true, lits));
+    }
+
+    #[inline]
+    fn flush(&mut self) {
+        self.record(|w| w.out.flush());
+    }
+}
+
+/// Reads a textual or binary DRAT proof. The input is binary if its first
+/// buffered bytes contain a byte that is neither printable nor whitespace,
+/// which holds for any binary proof as its clauses end with a zero byte.
+pub struct DratReader<R: BufRead> {
+    reader: R,
+    binary: Option<bool>,
+    line: usize,
+    pos: usize,
+    buf: String,
+    pending: Vec<(usize, String)>,
+    eof: bool,
+}
+
+impl<R: BufRead> DratReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            binary: None,
+            line: 0,
+            pos: 0,
+            buf: String::new(),
+            pending: Vec::new(),
+            eof: false,
+        }
+    }
+
+    fn io_error(line: usize, pos: usize) -> impl FnOnce(io::Error) -> DimacsError {
+        move |e| DimacsError::new(line, pos, DimacsErrorKind::Io(e))
+    }
+
+    fn detect(&mut self) -> Result<bool, DimacsError> {
+        if let Some(b) = self.binary {
+            return Ok(b);
+        }
+        let head = self.reader.fill_buf().map_err(Self::io_error(self.line, self.pos))?;
+        let binary = head
+            .iter()
+            .any(|b| !b.is_ascii_graphic() && !b.is_ascii_whitespace());
+        self.binary = Some(binary);
+        Ok(binary)
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>, DimacsError> {
+        let buf = self.reader.fill_buf().map_err(Self::io_error(self.line, self.pos))?;
+        let Some(&b) = buf.first() else {
+            return Ok(None);
+        };
+        self.reader.consume(1);
+        self.pos += 1;
+        Ok(Some(b))
+    }
+
+    fn next_binary(&mut self) -> Result<Option<ProofStep>, DimacsError> {
+        let start = self.pos + 1;
+        let delete = match self.read_byte()? {
+            None => return Ok(None),
+            Some(b'a') => false,
+            Some(b'd') => true,
+            Some(b) => {
+                return Err(DimacsError::new(
+                    0,
+                    start,
+                    DimacsErrorKind::InvalidLiteral(format!("{b:#x}")),
+                ));
+            }
+        };
+        let mut lits = LitVec::new();
+        loop {
+            let (mut u, mut shift) = (0u64, 0);
+            loop {
+                let Some(b) = self.read_byte()? else {
+                    return Err(DimacsError::new(
+                        0,
+                        start,
+                        DimacsErrorKind::UnterminatedClause,
+                    ));
+                };
+                u |= ((b & 0x7f) as u64) << shift;
+                shift += 7;
+                if b & 0x80 == 0 {
+                    break;
+                }
+                if shift > 35 {
+                    return Err(DimacsError::new(
+                        0,
+                        self.pos,
+                        DimacsErrorKind::InvalidLiteral(format!("{u}")),
+                    ));
+                }
+            }
+            if u == 0 {
+                break;
+            }
+            lits.push(Lit::new(Var((u >> 1) as u32), u & 1 == 0));
+        }
+        Ok(Some(if delete {
+            ProofStep::Delete(lits)
+        } else {
+            ProofStep::Add(lits)
+        }))
+    }
+
+    fn next_token(&mut self) -> Result<Option<(usize, String)>, DimacsError> {
+        while self.pending.is_empty() {
+            self.buf.clear();
+            let n = self
+                .reader
+                .read_line(&mut self.buf)
+                .map_err(Self::io_error(self.line, self.pos))?;
+            if n == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            let line = self.buf.as_str();
+            if line.trim_start().starts_with('c') {
+                continue;
+            }
+            self.pending = line
+                .split_ascii_whitespace()
+                .map(|t| {
+                    (
+                        t.as_ptr() as usize - line.as_ptr() as usize + 1,
+                        t.to_string(),
+                    )
+                })
+                .rev()
+                .collect();
+        }
+        Ok(self.pending.pop())
+    }
+
+    fn next_text(&mut self) -> Result<Option<ProofStep>, DimacsError> {
+        let Some((col, token)) = self.next_token()? else {
+            return Ok(None);
+        };
+        let start = (self.line, col);
+        let delete = token == "d";
+        let mut next = if delete {
+            self.next_token()?
+        } else {
+            Some((col, token))
+        };
+        let mut lits = LitVec::new();
+        loop {
+            let Some((col, token)) = next else {
+                return Err(DimacsError::new(
+                    start.0,
+                    start.1,
+                    DimacsErrorKind::UnterminatedClause,
+                ));
+            };
+            let invalid = || {
+                DimacsError::new(
+                    self.line,
+                    col,
+                    DimacsErrorKind::InvalidLiteral(token.clone()),
+                )
+            };
+            let lit: i64 = token.parse().map_err(|_| invalid())?;
+            if lit == 0 {
+                break;
+            }
+            match u32::try_from(lit.unsigned_abs()) {
+                Ok(v) if v < u32::MAX >> 1 => lits.push(Lit::new(Var(v), lit > 0)),
+                _ => return Err(invalid()),
+            }
+            next = self.next_token()?;
+        }
+        Ok(Some(if delete {
+            ProofStep::Delete(lits)
+        } else {
+            ProofStep::Add(lits)
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for DratReader<R> {
+    type Item = Result<ProofStep, DimacsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.eof {
+            return None;
+        }
+        let res = match self.detect() {
+            Ok(true) => self.next_binary(),
+            Ok(false) => self.next_text(),
+            Err(e) => Err(e),
+        };
+        match res {
+            Ok(Some(step)) => Some(Ok(step)),
+            Ok(None) => {
+                self.eof = true;
+                None
+            }
+            Err(e) => {
+                self.eof = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
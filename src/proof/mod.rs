@@ -0,0 +1,56 @@
+mod check;
+mod drat;
+
+pub use check::*;
+pub use drat::*;
+
+use crate::{Lit, LitVec};
+use std::{cell::RefCell, rc::Rc};
+
+/// A clausal proof step. Proof variables follow the `Cnf` numbering, that is
+/// `Var(k)` is DIMACS variable `k`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofStep {
+    Add(LitVec),
+    Delete(LitVec),
+}
+
+/// Receiver of the clausal proof emitted by a solver. For RAT additions the
+/// pivot literal must come first.
+pub trait ProofSink {
+    fn add(&mut self, lits: &[Lit]);
+
+    fn delete(&mut self, lits: &[Lit]);
+
+    #[inline]
+    fn flush(&mut self) {}
+}
+
+impl ProofSink for Vec<ProofStep> {
+    #[inline]
+    fn add(&mut self, lits: &[Lit]) {
+        self.push(ProofStep::Add(LitVec::from(lits)));
+    }
+
+    #[inline]
+    fn delete(&mut self, lits: &[Lit]) {
+        self.push(ProofStep::Delete(LitVec::from(lits)));
+    }
+}
+
+impl<S: ProofSink> ProofSink for Rc<RefCell<S>> {
+    #[inline]
+    fn add(&mut self, lits: &[Lit]) {
+        self.borrow_mut().add(lits);
+    }
+
+    #[inline]
+    fn delete(&mut self, lits: &[Lit]) {
+        self.borrow_mut().delete(lits);
+    }
+
+    #[inline]
+    fn flush(&mut self) {
+        self.borrow_mut().flush();
+    }
+}
@@ -0,0 +1,142 @@
+use super::{Satif, SatifFeatures};
+use crate::{Lit, LitVec, Var};
+
+type Enlarge<'a, S> = Box<dyn FnMut(&S, LitVec) -> LitVec + 'a>;
+
+/// Enumerates the models of a `Satif` projected onto `proj`. Each model is
+/// returned as a cube over the projection and then blocked. The blocking
+/// clauses are guarded by an activation variable, which is retired when the
+/// enumerator is dropped, so the solver can be reused afterwards.
+pub struct AllSat<'a, S: Satif + ?Sized> {
+    solver: &'a mut S,
+    proj: Vec<Var>,
+    assumps: LitVec,
+    enlarge: Option<Enlarge<'a, S>>,
+    done: bool,
+    num_model: usize,
+}
+
+impl<'a, S: Satif + ?Sized> AllSat<'a, S> {
+    pub fn new(solver: &'a mut S, proj: &[Var], assumps: &[Lit]) -> Self {
+        let act = solver.new_var();
+        if solver.features().contains(SatifFeatures::FROZEN) {
+            solver.set_frozen(act, true);
+        }
+        let mut a = LitVec::from(assumps);
+        a.push(act.lit());
+        Self {
+            solver,
+            proj: proj.to_vec(),
+            assumps: a,
+            enlarge: None,
+            done: false,
+            num_model: 0,
+        }
+    }
+
+    /// Sets a cube enlargement. It receives the solver holding the current
+    /// model and the full projected cube, and must return a subcube all of
+    /// whose extensions are models, see [`lift_cube`].
+    pub fn with_enlarge(mut self, enlarge: impl FnMut(&S, LitVec) -> LitVec + 'a) -> Self {
+        self.enlarge = Some(Box::new(enlarge));
+        self
+    }
+
+    #[inline]
+    pub fn num_model(&self) -> usize {
+        self.num_model
+    }
+
+    #[inline]
+    fn act(&self) -> Lit {
+        self.assumps.last()
+    }
+}
+
+impl<S: Satif + ?Sized> Iterator for AllSat<'_, S> {
+    type Item = LitVec;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if !self.solver.solve(&self.assumps) {
+            self.done = true;
+            return None;
+        }
+        let mut cube: LitVec = self
+            .proj
+            .iter()
+            .filter_map(|v| self.solver.sat_value_lit(*v))
+            .collect();
+        if let Some(enlarge) = self.enlarge.as_mut() {
+            cube = enlarge(self.solver, cube);
+        }
+        let mut block = !&cube;
+        block.push(!self.act());
+        self.solver.add_clause(&block);
+        self.num_model += 1;
+        Some(cube)
+    }
+}
+
+impl<S: Satif + ?Sized> Drop for AllSat<'_, S> {
+    fn drop(&mut self) {
+        let act = self.act();
+        self.solver.add_clause(&[!act]);
+        if self.solver.features().contains(SatifFeatures::FROZEN) {
+            self.solver.set_frozen(act.var(), false);
+        }
+    }
+}
+
+/// Lifts `cube` with a solver holding the formula conjoined with the negated
+/// target: if it is unsatisfiable under `cube` and `fixed`, the literals of
+/// `cube` in the failed assumptions still guarantee the target. Otherwise
+/// `cube` is returned unchanged.
+pub fn lift_cube<L: Satif + ?Sized>(lift: &mut L, cube: LitVec, fixed: &[Lit]) -> LitVec {
+    if !lift.features().contains(SatifFeatures::UNSAT_CORE) {
+        return cube;
+    }
+    let mut assumps = cube.clone();
+    assumps.extend_from_slice(fixed);
+    if lift.solve(&assumps) {
+        return cube;
+    }
+    cube.into_iter().filter(|l| lift.unsat_has(*l)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DagCnf, satif::CdclSolver};
+
+    #[test]
+    fn test_allsat() {
+        let mut dc = DagCnf::new();
+        let (a, b, c) = (dc.new_var().lit(), dc.new_var().lit(), dc.new_var().lit());
+        let bc = dc.new_or([b, c]);
+        let o = dc.new_and([a, bc]);
+        let mut solver = CdclSolver::new();
+        let mut lift = CdclSolver::new();
+        for cls in dc.clause() {
+            solver.add_clause(cls);
+            lift.add_clause(cls);
+        }
+        lift.add_clause(&[!o]);
+        let proj = [a.var(), b.var(), c.var()];
+        let models: Vec<LitVec> = AllSat::new(&mut solver, &proj, &[o]).collect();
+        assert_eq!(models.len(), 3);
+        let cubes: Vec<LitVec> = AllSat::new(&mut solver, &proj, &[o])
+            .with_enlarge(|_, cube| lift_cube(&mut lift, cube, &[]))
+            .collect();
+        assert_eq!(cubes.len(), 2);
+        for m in models.iter() {
+            assert!(cubes.iter().any(|c| c.iter().all(|l| m.contains(l))));
+        }
+        for c in cubes.iter() {
+            assert!(!solver.solve(&[c.as_slice(), &[!o]].concat()));
+        }
+        assert!(solver.solve(&[o]));
+    }
+}
@@ -0,0 +1,974 @@
+use super::{Satif, SatifFeatures};
+use crate::{Lbool, Lit, LitMap, LitSet, LitVec, Var, VarAssign, VarMap, proof::ProofSink};
+use std::{
+    mem::take,
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
+
+type ClauseRef = u32;
+
+/// The proof sink of a solver. A clone of the solver does not inherit it.
+#[derive(Default)]
+struct ProofSlot(Option<Box<dyn ProofSink>>);
+
+impl Clone for ProofSlot {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl Deref for ProofSlot {
+    type Target = Option<Box<dyn ProofSink>>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for ProofSlot {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Clause {
+    lits: LitVec,
+    learnt: bool,
+    removed: bool,
+    act: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Watcher {
+    cref: ClauseRef,
+    blocker: Lit,
+}
+
+#[derive(Debug, Default, Clone)]
+struct VarOrder {
+    heap: Vec<Var>,
+    pos: VarMap<Option<usize>>,
+}
+
+impl VarOrder {
+    #[inline]
+    fn reserve(&mut self, var: Var) {
+        self.pos.reserve(var);
+    }
+
+    #[inline]
+    fn contains(&self, var: Var) -> bool {
+        self.pos[var].is_some()
+    }
+
+    fn up(&mut self, mut i: usize, act: &VarMap<f64>) {
+        let v = self.heap[i];
+        while i > 0 {
+            let p = (i - 1) / 2;
+            if act[self.heap[p]] >= act[v] {
+                break;
+            }
+            self.heap[i] = self.heap[p];
+            self.pos[self.heap[i]] = Some(i);
+            i = p;
+        }
+        self.heap[i] = v;
+        self.pos[v] = Some(i);
+    }
+
+    fn down(&mut self, mut i: usize, act: &VarMap<f64>) {
+        let v = self.heap[i];
+        loop {
+            let l = 2 * i + 1;
+            if l >= self.heap.len() {
+                break;
+            }
+            let r = l + 1;
+            let c = if r < self.heap.len() && act[self.heap[r]] > act[self.heap[l]] {
+                r
+            } else {
+                l
+            };
+            if act[self.heap[c]] <= act[v] {
+                break;
+            }
+            self.heap[i] = self.heap[c];
+            self.pos[self.heap[i]] = Some(i);
+            i = c;
+        }
+        self.heap[i] = v;
+        self.pos[v] = Some(i);
+    }
+
+    #[inline]
+    fn insert(&mut self, var: Var, act: &VarMap<f64>) {
+        if !self.contains(var) {
+            self.heap.push(var);
+            self.up(self.heap.len() - 1, act);
+        }
+    }
+
+    #[inline]
+    fn increase(&mut self, var: Var, act: &VarMap<f64>) {
+        if let Some(i) = self.pos[var] {
+            self.up(i, act);
+        }
+    }
+
+    fn pop(&mut self, act: &VarMap<f64>) -> Option<Var> {
+        let v = *self.heap.first()?;
+        let last = self.heap.pop().unwrap();
+        self.pos[v] = None;
+        if !self.heap.is_empty() {
+            self.heap[0] = last;
+            self.down(0, act);
+        }
+        Some(v)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CdclStatistic {
+    pub num_solve: usize,
+    pub num_conflict: usize,
+    pub num_decision: usize,
+    pub num_propagation: usize,
+    pub num_restart: usize,
+}
+
+/// A reference CDCL solver with two watched literals, 1UIP clause learning,
+/// VSIDS branching, Luby restarts and phase saving. `Var::CONST` is created
+/// on construction and fixed to false, so `Cnf` and `DagCnf` clauses can be
+/// added directly. A DRAT proof of unsatisfiability can be recorded with
+/// [`CdclSolver::set_proof`], a clone of the solver records no proof.
+#[derive(Clone)]
+pub struct CdclSolver {
+    clauses: Vec<Clause>,
+    num_removed: usize,
+    num_learnt: usize,
+    watches: LitMap<Vec<Watcher>>,
+    value: VarAssign,
+    level: VarMap<u32>,
+    reason: VarMap<Option<ClauseRef>>,
+    phase: VarMap<bool>,
+    seen: VarMap<bool>,
+    activity: VarMap<f64>,
+    order: VarOrder,
+    trail: Vec<Lit>,
+    trail_lim: Vec<usize>,
+    qhead: usize,
+    assumps: LitVec,
+    num_var: usize,
+    ok: bool,
+    var_inc: f64,
+    cla_inc: f64,
+    max_learnt: f64,
+    model: VarAssign,
+    model_num_var: usize,
+    failed: LitSet,
+    proof: ProofSlot,
+    statistic: CdclStatistic,
+}
+
+impl CdclSolver {
+    const VAR_DECAY: f64 = 0.95;
+    const CLA_DECAY: f64 = 0.999;
+    const RESTART_FIRST: f64 = 100.0;
+
+    pub fn new() -> Self {
+        let mut res = Self {
+            clauses: Vec::new(),
+            num_removed: 0,
+            num_learnt: 0,
+            watches: LitMap::new(),
+            value: VarAssign::new(),
+            level: VarMap::new(),
+            reason: VarMap::new(),
+            phase: VarMap::new(),
+            seen: VarMap::new(),
+            activity: VarMap::new(),
+            order: VarOrder::default(),
+            trail: Vec::new(),
+            trail_lim: Vec::new(),
+            qhead: 0,
+            assumps: LitVec::new(),
+            num_var: 0,
+            ok: true,
+            var_inc: 1.0,
+            cla_inc: 1.0,
+            max_learnt: 0.0,
+            model: VarAssign::new(),
+            model_num_var: 0,
+            failed: LitSet::new(),
+            proof: ProofSlot::default(),
+            statistic: CdclStatistic::default(),
+        };
+        let c = res.new_var();
+        debug_assert!(c == Var::CONST);
+        res
+    }
+
+    #[inline]
+    pub fn statistic(&self) -> &CdclStatistic {
+        &self.statistic
+    }
+
+    /// Records learnt and deleted clauses to `proof` from now on. Clauses
+    /// added through `add_clause` are the original formula and not recorded.
+    #[inline]
+    pub fn set_proof(&mut self, proof: impl ProofSink + 'static) {
+        *self.proof = Some(Box::new(proof));
+    }
+
+    #[inline]
+    pub fn take_proof(&mut self) -> Option<Box<dyn ProofSink>> {
+        let mut proof = self.proof.take()?;
+        proof.flush();
+        Some(proof)
+    }
+
+    #[inline]
+    fn proof_add(&mut self, lits: &[Lit]) {
+        if let Some(p) = self.proof.as_mut() {
+            p.add(lits);
+        }
+    }
+
+    #[inline]
+    fn v(&self, lit: Lit) -> Lbool {
+        self.value.v(lit)
+    }
+
+    #[inline]
+    fn decision_level(&self) -> u32 {
+        self.trail_lim.len() as u32
+    }
+
+    #[inline]
+    fn enqueue(&mut self, lit: Lit, reason: Option<ClauseRef>) {
+        debug_assert!(self.v(lit).is_none());
+        let v = lit.var();
+        self.value.set(lit);
+        self.level[v] = self.decision_level();
+        self.reason[v] = reason;
+        self.trail.push(lit);
+    }
+
+    #[inline]
+    fn new_decision_level(&mut self) {
+        self.trail_lim.push(self.trail.len());
+    }
+
+    fn cancel_until(&mut self, level: u32) {
+        if self.decision_level() <= level {
+            return;
+        }
+        let lim = self.trail_lim[level as usize];
+        for i in (lim..self.trail.len()).rev() {
+            let l = self.trail[i];
+            let v = l.var();
+            self.value.set_none(v);
+            self.reason[v] = None;
+            self.phase[v] = l.polarity();
+            self.order.insert(v, &self.activity);
+        }
+        self.trail.truncate(lim);
+        self.trail_lim.truncate(level as usize);
+        self.qhead = lim;
+    }
+
+    fn attach(&mut self, cref: ClauseRef) {
+        let c = &self.clauses[cref as usize].lits;
+        debug_assert!(c.len() > 1);
+        let (c0, c1) = (c[0], c[1]);
+        self.watches[!c0].push(Watcher { cref, blocker: c1 });
+        self.watches[!c1].push(Watcher { cref, blocker: c0 });
+    }
+
+    fn alloc(&mut self, lits: LitVec, learnt: bool) -> ClauseRef {
+        let cref = self.clauses.len() as ClauseRef;
+        self.clauses.push(Clause {
+            lits,
+            learnt,
+            removed: false,
+            act: 0.0,
+        });
+        if learnt {
+            self.num_learnt += 1;
+        }
+        self.attach(cref);
+        cref
+    }
+
+    fn propagate(&mut self) -> Option<ClauseRef> {
+        let mut conflict = None;
+        while conflict.is_none() && self.qhead < self.trail.len() {
+            let p = self.trail[self.qhead];
+            self.qhead += 1;
+            self.statistic.num_propagation += 1;
+            let false_lit = !p;
+            let mut ws = take(&mut self.watches[p]);
+            let (mut i, mut j) = (0, 0);
+            'w: while i < ws.len() {
+                let w = ws[i];
+                i += 1;
+                if self.v(w.blocker).is_true() {
+                    ws[j] = w;
+                    j += 1;
+                    continue;
+                }
+                let c = &mut self.clauses[w.cref as usize].lits;
+                if c[0] == false_lit {
+                    c.swap(0, 1);
+                }
+                let first = c[0];
+                let nw = Watcher {
+                    cref: w.cref,
+                    blocker: first,
+                };
+                if first != w.blocker && self.value.v(first).is_true() {
+                    ws[j] = nw;
+                    j += 1;
+                    continue;
+                }
+                for k in 2..c.len() {
+                    if !self.value.v(c[k]).is_false() {
+                        c.swap(1, k);
+                        let nl = !c[1];
+                        self.watches[nl].push(nw);
+                        continue 'w;
+                    }
+                }
+                ws[j] = nw;
+                j += 1;
+                if self.v(first).is_false() {
+                    conflict = Some(w.cref);
+                    while i < ws.len() {
+                        ws[j] = ws[i];
+                        i += 1;
+                        j += 1;
+                    }
+                } else {
+                    self.enqueue(first, Some(w.cref));
+                }
+            }
+            ws.truncate(j);
+            self.watches[p] = ws;
+        }
+        conflict
+    }
+
+    fn var_bump(&mut self, v: Var) {
+        self.activity[v] += self.var_inc;
+        if self.activity[v] > 1e100 {
+            for a in self.activity.iter_mut() {
+                *a *= 1e-100;
+            }
+            self.var_inc *= 1e-100;
+        }
+        self.order.increase(v, &self.activity);
+    }
+
+    fn cla_bump(&mut self, cref: ClauseRef) {
+        let c = &mut self.clauses[cref as usize];
+        if !c.learnt {
+            return;
+        }
+        c.act += self.cla_inc;
+        if c.act > 1e20 {
+            for c in self.clauses.iter_mut().filter(|c| c.learnt) {
+                c.act *= 1e-20;
+            }
+            self.cla_inc *= 1e-20;
+        }
+    }
+
+    fn analyze(&mut self, mut confl: ClauseRef) -> (LitVec, u32) {
+        let mut learnt = LitVec::from([Lit::default()]);
+        let mut path_c = 0;
+        let mut p: Option<Lit> = None;
+        let mut index = self.trail.len();
+        loop {
+            self.cla_bump(confl);
+            let start = p.is_some() as usize;
+            for k in start..self.clauses[confl as usize].lits.len() {
+                let q = self.clauses[confl as usize].lits[k];
+                let qv = q.var();
+                if !self.seen[qv] && self.level[qv] > 0 {
+                    self.var_bump(qv);
+                    self.seen[qv] = true;
+                    if self.level[qv] >= self.decision_level() {
+                        path_c += 1;
+                    } else {
+                        learnt.push(q);
+                    }
+                }
+            }
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].var()] {
+                    break;
+                }
+            }
+            let pl = self.trail[index];
+            p = Some(pl);
+            self.seen[pl.var()] = false;
+            path_c -= 1;
+            if path_c == 0 {
+                break;
+            }
+            confl = self.reason[pl.var()].unwrap();
+        }
+        learnt[0] = !p.unwrap();
+        let mut minimized = LitVec::from([learnt[0]]);
+        for &l in learnt[1..].iter() {
+            let redundant = self.reason[l.var()].is_some_and(|r| {
+                self.clauses[r as usize].lits[1..]
+                    .iter()
+                    .all(|q| self.seen[q.var()] || self.level[q.var()] == 0)
+            });
+            if !redundant {
+                minimized.push(l);
+            }
+        }
+        for l in learnt[1..].iter() {
+            self.seen[l.var()] = false;
+        }
+        let mut bt = 0;
+        if minimized.len() > 1 {
+            let mut max_i = 1;
+            for i in 2..minimized.len() {
+                if self.level[minimized[i].var()] > self.level[minimized[max_i].var()] {
+                    max_i = i;
+                }
+            }
+            minimized.swap(1, max_i);
+            bt = self.level[minimized[1].var()];
+        }
+        (minimized, bt)
+    }
+
+    fn analyze_final(&mut self, p: Lit) {
+        self.failed.clear();
+        self.failed.insert(p);
+        if self.decision_level() == 0 {
+            return;
+        }
+        self.seen[p.var()] = true;
+        for i in (self.trail_lim[0]..self.trail.len()).rev() {
+            let x = self.trail[i];
+            let xv = x.var();
+            if !self.seen[xv] {
+                continue;
+            }
+            match self.reason[xv] {
+                None => {
+                    debug_assert!(self.level[xv] > 0);
+                    self.failed.insert(x);
+                }
+                Some(r) => {
+                    for k in 1..self.clauses[r as usize].lits.len() {
+                        let q = self.clauses[r as usize].lits[k];
+                        if self.level[q.var()] > 0 {
+                            self.seen[q.var()] = true;
+                        }
+                    }
+                }
+            }
+            self.seen[xv] = false;
+        }
+        self.seen[p.var()] = false;
+    }
+
+    #[inline]
+    fn locked(&self, cref: ClauseRef) -> bool {
+        let c0 = self.clauses[cref as usize].lits[0];
+        self.reason[c0.var()] == Some(cref) && self.v(c0).is_true()
+    }
+
+    fn remove_clause(&mut self, cref: ClauseRef) {
+        if self.proof.is_some() {
+            let c0 = self.clauses[cref as usize].lits[0];
+            if self.locked(cref) {
+                self.proof_add(&[c0]);
+            }
+            let proof = self.proof.as_mut().unwrap();
+            proof.delete(&self.clauses[cref as usize].lits);
+        }
+        let c = &mut self.clauses[cref as usize];
+        debug_assert!(!c.removed);
+        c.removed = true;
+        c.lits = LitVec::new();
+        if c.learnt {
+            self.num_learnt -= 1;
+        }
+        self.num_removed += 1;
+    }
+
+    fn garbage_collect(&mut self) {
+        if self.num_removed * 2 <= self.clauses.len() {
+            for ws in self.watches.iter_mut() {
+                ws.retain(|w| !self.clauses[w.cref as usize].removed);
+            }
+            return;
+        }
+        let mut map = vec![None; self.clauses.len()];
+        let mut clauses = Vec::with_capacity(self.clauses.len() - self.num_removed);
+        for (i, c) in take(&mut self.clauses).into_iter().enumerate() {
+            if !c.removed {
+                map[i] = Some(clauses.len() as ClauseRef);
+                clauses.push(c);
+            }
+        }
+        self.clauses = clauses;
+        self.num_removed = 0;
+        for l in self.trail.iter() {
+            let r = &mut self.reason[l.var()];
+            *r = r.and_then(|r| map[r as usize]);
+        }
+        for ws in self.watches.iter_mut() {
+            ws.clear();
+        }
+        for cref in 0..self.clauses.len() as ClauseRef {
+            self.attach(cref);
+        }
+    }
+
+    fn reduce_db(&mut self) {
+        let mut learnts: Vec<ClauseRef> = (0..self.clauses.len() as ClauseRef)
+            .filter(|&c| {
+                let c = &self.clauses[c as usize];
+                c.learnt && !c.removed && c.lits.len() > 2
+            })
+            .collect();
+        learnts.sort_by(|a, b| {
+            self.clauses[*a as usize]
+                .act
+                .total_cmp(&self.clauses[*b as usize].act)
+        });
+        let extra_lim = self.cla_inc / learnts.len().max(1) as f64;
+        for (i, &c) in learnts.iter().enumerate() {
+            if !self.locked(c)
+                && (i < learnts.len() / 2 || self.clauses[c as usize].act < extra_lim)
+            {
+                self.remove_clause(c);
+            }
+        }
+        self.garbage_collect();
+    }
+
+    fn simplify_db(&mut self) {
+        debug_assert!(self.decision_level() == 0);
+        for cref in 0..self.clauses.len() as ClauseRef {
+            let c = &self.clauses[cref as usize];
+            if !c.removed && c.lits.iter().any(|l| self.v(*l).is_true()) {
+                self.remove_clause(cref);
+            }
+        }
+        self.garbage_collect();
+    }
+
+    fn pick_branch_lit(&mut self) -> Option<Lit> {
+        loop {
+            let v = self.order.pop(&self.activity)?;
+            if self.value.vl(v).is_none() {
+                self.statistic.num_decision += 1;
+                return Some(Lit::new(v, self.phase[v]));
+            }
+        }
+    }
+
+    fn search(&mut self, num_conflict: usize, deadline: Option<Instant>) -> Lbool {
+        let mut conflicts = 0;
+        loop {
+            if let Some(confl) = self.propagate() {
+                self.statistic.num_conflict += 1;
+                conflicts += 1;
+                if self.decision_level() == 0 {
+                    self.proof_add(&[]);
+                    return Lbool::FALSE;
+                }
+                let (learnt, bt) = self.analyze(confl);
+                self.proof_add(&learnt);
+                self.cancel_until(bt);
+                if learnt.len() == 1 {
+                    self.enqueue(learnt[0], None);
+                } else {
+                    let l0 = learnt[0];
+                    let cref = self.alloc(learnt, true);
+                    self.cla_bump(cref);
+                    self.enqueue(l0, Some(cref));
+                }
+                self.var_inc /= Self::VAR_DECAY;
+                self.cla_inc /= Self::CLA_DECAY;
+            } else {
+                if conflicts >= num_conflict || deadline.is_some_and(|d| Instant::now() >= d) {
+                    self.cancel_until(0);
+                    return Lbool::NONE;
+                }
+                if self.num_learnt as f64 >= self.max_learnt + self.trail.len() as f64 {
+                    self.reduce_db();
+                }
+                let mut next = None;
+                while (self.decision_level() as usize) < self.assumps.len() {
+                    let p = self.assumps[self.decision_level() as usize];
+                    let pv = self.v(p);
+                    if pv.is_true() {
+                        self.new_decision_level();
+                    } else if pv.is_false() {
+                        self.analyze_final(p);
+                        return Lbool::FALSE;
+                    } else {
+                        next = Some(p);
+                        break;
+                    }
+                }
+                let next = match next {
+                    Some(n) => n,
+                    None => match self.pick_branch_lit() {
+                        Some(n) => n,
+                        None => return Lbool::TRUE,
+                    },
+                };
+                self.new_decision_level();
+                self.enqueue(next, None);
+            }
+        }
+    }
+
+    fn solve_inner(&mut self, assumps: &[Lit], deadline: Option<Instant>) -> Lbool {
+        self.statistic.num_solve += 1;
+        self.failed.clear();
+        self.model_num_var = 0;
+        if !self.ok {
+            return Lbool::FALSE;
+        }
+        if let Some(m) = assumps.iter().map(|l| l.var()).max() {
+            self.new_var_to(m);
+        }
+        self.assumps = LitVec::from(assumps);
+        self.max_learnt = self
+            .max_learnt
+            .max((self.clauses.len() - self.num_learnt) as f64 / 3.0);
+        let mut res = Lbool::NONE;
+        let mut restart = 0;
+        while res.is_none() {
+            let num_conflict = (luby(2.0, restart) * Self::RESTART_FIRST) as usize;
+            res = self.search(num_conflict, deadline);
+            if res.is_none() {
+                if deadline.is_some_and(|d| Instant::now() >= d) {
+                    break;
+                }
+                restart += 1;
+                self.statistic.num_restart += 1;
+                self.max_learnt *= 1.1;
+            }
+        }
+        if res.is_true() {
+            self.model = self.value.clone();
+            self.model_num_var = self.num_var;
+        } else if res.is_false() && self.failed.elements().is_empty() {
+            self.ok = false;
+        }
+        self.cancel_until(0);
+        self.assumps.clear();
+        res
+    }
+
+    fn add_constraint(&mut self, constraint: Vec<LitVec>) -> Option<Lit> {
+        if constraint.is_empty() {
+            return None;
+        }
+        let act = self.new_var().lit();
+        for mut c in constraint {
+            c.push(!act);
+            if self.proof.is_some() {
+                let n = c.len() - 1;
+                c.swap(0, n);
+                self.proof_add(&c);
+            }
+            self.add_clause(&c);
+        }
+        Some(act)
+    }
+
+    fn solve_constrained(
+        &mut self,
+        assumps: &[Lit],
+        constraint: Vec<LitVec>,
+        deadline: Option<Instant>,
+    ) -> Lbool {
+        let Some(act) = self.add_constraint(constraint) else {
+            return self.solve_inner(assumps, deadline);
+        };
+        let mut a = LitVec::from(assumps);
+        a.push(act);
+        let res = self.solve_inner(&a, deadline);
+        self.proof_add(&[!act]);
+        self.add_clause(&[!act]);
+        res
+    }
+}
+
+impl Default for CdclSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Satif for CdclSolver {
+    fn new_var(&mut self) -> Var {
+        let v = Var::new(self.num_var);
+        self.num_var += 1;
+        self.watches.reserve(v);
+        self.value.reserve(v);
+        self.level.reserve(v);
+        self.reason.reserve(v);
+        self.phase.reserve(v);
+        self.seen.reserve(v);
+        self.activity.reserve(v);
+        self.order.reserve(v);
+        self.failed.reserve(v);
+        if v.is_constant() {
+            debug_assert!(self.v(Lit::constant(true)).is_true());
+            self.trail.push(Lit::constant(true));
+        } else {
+            self.order.insert(v, &self.activity);
+        }
+        v
+    }
+
+    #[inline]
+    fn num_var(&self) -> usize {
+        self.num_var
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.cancel_until(0);
+        if !self.ok {
+            return;
+        }
+        let mut cls = LitVec::from(clause);
+        if let Some(m) = cls.iter().map(|l| l.var()).max() {
+            self.new_var_to(m);
+        }
+        cls.sort();
+        cls.dedup();
+        let (mut i, len) = (0, cls.len());
+        while i < cls.len() {
+            if self.v(cls[i]).is_true() || (i + 1 < cls.len() && cls[i + 1] == !cls[i]) {
+                return;
+            }
+            if self.v(cls[i]).is_false() {
+                cls.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        if cls.len() < len {
+            self.proof_add(&cls);
+        }
+        match cls.len() {
+            0 => self.ok = false,
+            1 => {
+                self.enqueue(cls[0], None);
+                self.ok = self.propagate().is_none();
+                if !self.ok {
+                    self.proof_add(&[]);
+                }
+            }
+            _ => {
+                self.alloc(cls, false);
+            }
+        }
+    }
+
+    #[inline]
+    fn solve(&mut self, assumps: &[Lit]) -> bool {
+        self.solve_inner(assumps, None).is_true()
+    }
+
+    #[inline]
+    fn solve_with_constraint(&mut self, assumps: &[Lit], constraint: Vec<LitVec>) -> bool {
+        self.solve_constrained(assumps, constraint, None).is_true()
+    }
+
+    #[inline]
+    fn solve_with_limit(
+        &mut self,
+        assumps: &[Lit],
+        constraint: Vec<LitVec>,
+        limit: Duration,
+    ) -> Option<bool> {
+        let deadline = Instant::now() + limit;
+        self.solve_constrained(assumps, constraint, Some(deadline))
+            .into()
+    }
+
+    #[inline]
+    fn sat_value(&self, lit: Lit) -> Option<bool> {
+        if (*lit.var() as usize) < self.model_num_var {
+            self.model.v(lit).into()
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn unsat_has(&self, lit: Lit) -> bool {
+        self.failed.has(lit)
+    }
+
+    fn simplify(&mut self) -> Option<bool> {
+        self.cancel_until(0);
+        if self.ok && self.propagate().is_some() {
+            self.ok = false;
+            self.proof_add(&[]);
+        }
+        if !self.ok {
+            return Some(false);
+        }
+        self.simplify_db();
+        None
+    }
+
+    /// Every variable is frozen, `simplify` only drops satisfied clauses and
+    /// never eliminates variables.
+    #[inline]
+    fn set_frozen(&mut self, _var: Var, _frozen: bool) {}
+
+    #[inline]
+    fn features(&self) -> SatifFeatures {
+        SatifFeatures::ALL
+    }
+
+    fn clauses(&self) -> Vec<LitVec> {
+        if !self.ok {
+            return vec![LitVec::new()];
+        }
+        let mut res: Vec<LitVec> = self
+            .trail
+            .iter()
+            .take(self.trail_lim.first().copied().unwrap_or(self.trail.len()))
+            .map(|l| LitVec::from(*l))
+            .collect();
+        res.extend(
+            self.clauses
+                .iter()
+                .filter(|c| !c.learnt && !c.removed)
+                .map(|c| c.lits.clone()),
+        );
+        res
+    }
+}
+
+fn luby(y: f64, mut x: usize) -> f64 {
+    let (mut size, mut seq) = (1, 0);
+    while size < x + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+    while size - 1 != x {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        x %= size;
+    }
+    y.powi(seq)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::DagCnf;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn brute_force(num_var: usize, cnf: &[LitVec], assumps: &[Lit]) -> bool {
+        (0..1u32 << num_var).any(|m| {
+            let v = |l: &Lit| ((m >> (*l.var() - 1)) & 1 == 1) == l.polarity();
+            assumps.iter().all(v) && cnf.iter().all(|c| c.iter().any(v))
+        })
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..300 {
+            let num_var = rng.random_range(1..=10);
+            let num_cls = rng.random_range(1..=45);
+            let rand_lit =
+                |rng: &mut StdRng| Lit::new(Var(rng.random_range(1..=num_var)), rng.random());
+            let cnf: Vec<LitVec> = (0..num_cls)
+                .map(|_| (0..3).map(|_| rand_lit(&mut rng)).collect())
+                .collect();
+            let assumps: LitVec = (0..rng.random_range(0..4))
+                .map(|_| rand_lit(&mut rng))
+                .collect();
+            let mut solver = CdclSolver::new();
+            for c in cnf.iter() {
+                solver.add_clause(c);
+            }
+            let res = solver.solve(&assumps);
+            assert_eq!(res, brute_force(num_var as usize, &cnf, &assumps));
+            if res {
+                let vars: Vec<Var> = (1..=num_var).map(Var).collect();
+                let model = solver.model(&vars);
+                assert!(model.check(cnf.iter()).is_ok());
+                for l in assumps.iter() {
+                    assert_eq!(solver.sat_value(*l), Some(true));
+                }
+            } else {
+                let core: LitVec = assumps
+                    .iter()
+                    .filter(|l| solver.unsat_has(**l))
+                    .copied()
+                    .collect();
+                assert!(!brute_force(num_var as usize, &cnf, &core));
+            }
+            assert_eq!(solver.solve(&[]), brute_force(num_var as usize, &cnf, &[]));
+        }
+    }
+
+    #[test]
+    fn test_dagcnf_model() {
+        let mut dc = DagCnf::new();
+        let (a, b) = (dc.new_var().lit(), dc.new_var().lit());
+        let o = dc.new_and([a, !b]);
+        let mut solver = CdclSolver::new();
+        for c in dc.clause() {
+            solver.add_clause(c);
+        }
+        assert!(solver.solve(&[o]));
+        let vars: Vec<Var> = dc.var_iter().collect();
+        let mut model = solver.model(&vars);
+        assert!(dc.check_model(&model).is_ok());
+        model.set(b);
+        assert!(dc.check_model(&model).unwrap_err().contains(&!b));
+    }
+
+    #[test]
+    fn test_clone() {
+        let proof = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let mut solver = CdclSolver::new();
+        solver.set_proof(proof.clone());
+        let (a, b) = (solver.new_var().lit(), solver.new_var().lit());
+        for c in [[a, b], [!a, b], [a, !b]] {
+            solver.add_clause(&c);
+        }
+        let mut clone = solver.clone();
+        clone.add_clause(&[!a, !b]);
+        assert!(!clone.solve(&[]));
+        assert!(clone.take_proof().is_none());
+        assert!(proof.borrow().is_empty());
+        assert!(solver.solve(&[]));
+        assert_eq!(
+            (solver.sat_value(a), solver.sat_value(b)),
+            (Some(true), Some(true))
+        );
+        assert!(solver.take_proof().is_some());
+    }
+}
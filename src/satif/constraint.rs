@@ -0,0 +1,254 @@
+use super::{Satif, SatifFeatures};
+use crate::{Lit, LitVec, Var};
+use std::time::Duration;
+
+/// A `Satif` wrapper that implements temporary constraints on top of any
+/// backend. Each constrained solve guards the constraint clauses with a fresh
+/// activation literal, which is retired by a unit clause before the next
+/// solve, clause or simplification, so the model and the failed assumptions
+/// of the constrained solve stay readable. Every `simplify_interval` retired
+/// activation variables the inner solver is simplified, if it supports it,
+/// to collect the dead clauses.
+pub struct ConstraintSatif<S: Satif> {
+    solver: S,
+    simplify_interval: usize,
+    num_retired: usize,
+    pending: Option<Var>,
+}
+
+impl<S: Satif> ConstraintSatif<S> {
+    #[inline]
+    pub fn new(solver: S) -> Self {
+        Self::new_with_interval(solver, 1000)
+    }
+
+    #[inline]
+    pub fn new_with_interval(solver: S, simplify_interval: usize) -> Self {
+        Self {
+            solver,
+            simplify_interval,
+            num_retired: 0,
+            pending: None,
+        }
+    }
+
+    #[inline]
+    pub fn num_retired(&self) -> usize {
+        self.num_retired
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.solver
+    }
+
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.solver
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.solver
+    }
+
+    fn retire_pending(&mut self) {
+        let Some(act) = self.pending.take() else {
+            return;
+        };
+        retire(&mut self.solver, act);
+        self.num_retired += 1;
+        if self.num_retired.is_multiple_of(self.simplify_interval) {
+            let _ = self.solver.try_simplify();
+        }
+    }
+}
+
+/// Guards the `constraint` clauses with a fresh activation literal, returning
+/// `assumps` extended by it and its variable. No variable is created for an
+/// empty constraint.
+pub(super) fn activate<S: Satif + ?Sized>(
+    solver: &mut S,
+    assumps: &[Lit],
+    constraint: Vec<LitVec>,
+) -> (LitVec, Option<Var>) {
+    let mut assumps = LitVec::from(assumps);
+    if constraint.is_empty() {
+        return (assumps, None);
+    }
+    let act = solver.new_var();
+    if solver.features().contains(SatifFeatures::FROZEN) {
+        solver.set_frozen(act, true);
+    }
+    for mut c in constraint {
+        c.push(!act.lit());
+        solver.add_clause(&c);
+    }
+    assumps.push(act.lit());
+    (assumps, Some(act))
+}
+
+/// Permanently disables the clauses guarded by the activation variable `act`.
+/// Adding the unit clause discards the last model on most backends.
+pub(super) fn retire<S: Satif + ?Sized>(solver: &mut S, act: Var) {
+    solver.add_clause(&[!act.lit()]);
+    if solver.features().contains(SatifFeatures::FROZEN) {
+        solver.set_frozen(act, false);
+    }
+}
+
+impl<S: Satif> Satif for ConstraintSatif<S> {
+    #[inline]
+    fn new_var(&mut self) -> Var {
+        self.solver.new_var()
+    }
+
+    #[inline]
+    fn num_var(&self) -> usize {
+        self.solver.num_var()
+    }
+
+    #[inline]
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.retire_pending();
+        self.solver.add_clause(clause);
+    }
+
+    #[inline]
+    fn solve(&mut self, assumps: &[Lit]) -> bool {
+        self.retire_pending();
+        self.solver.solve(assumps)
+    }
+
+    fn solve_with_constraint(&mut self, assumps: &[Lit], constraint: Vec<LitVec>) -> bool {
+        self.retire_pending();
+        let (assumps, act) = activate(&mut self.solver, assumps, constraint);
+        let res = self.solver.solve(&assumps);
+        self.pending = act;
+        res
+    }
+
+    fn solve_with_limit(
+        &mut self,
+        assumps: &[Lit],
+        constraint: Vec<LitVec>,
+        limit: Duration,
+    ) -> Option<bool> {
+        self.retire_pending();
+        let (assumps, act) = activate(&mut self.solver, assumps, constraint);
+        let res = self.solver.solve_with_limit(&assumps, vec![], limit);
+        self.pending = act;
+        res
+    }
+
+    #[inline]
+    fn features(&self) -> SatifFeatures {
+        self.solver.features() | SatifFeatures::CONSTRAINT
+    }
+
+    #[inline]
+    fn sat_value(&self, lit: Lit) -> Option<bool> {
+        self.solver.sat_value(lit)
+    }
+
+    #[inline]
+    fn unsat_has(&self, lit: Lit) -> bool {
+        self.solver.unsat_has(lit)
+    }
+
+    #[inline]
+    fn simplify(&mut self) -> Option<bool> {
+        self.retire_pending();
+        self.solver.simplify()
+    }
+
+    #[inline]
+    fn set_frozen(&mut self, var: Var, frozen: bool) {
+        self.solver.set_frozen(var, frozen)
+    }
+
+    #[inline]
+    fn clauses(&self) -> Vec<LitVec> {
+        self.solver.clauses()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::satif::CdclSolver;
+
+    #[test]
+    fn test_constraint() {
+        let mut s = ConstraintSatif::new_with_interval(CdclSolver::new(), 2);
+        let (a, b, c) = (s.new_var().lit(), s.new_var().lit(), s.new_var().lit());
+        s.add_clause(&[a, b]);
+        s.add_clause(&[!a, c]);
+        for _ in 0..3 {
+            assert!(!s.solve_with_constraint(&[!c], vec![LitVec::from([!b])]));
+            assert!(s.solve_with_constraint(&[!c], vec![LitVec::from([b, a])]));
+            assert_eq!(s.sat_value(b), Some(true));
+        }
+        assert_eq!(s.num_retired(), 5);
+        assert!(s.solve(&[!b]));
+        assert_eq!(s.num_retired(), 6);
+        assert_eq!(
+            s.solve_with_limit(
+                &[],
+                vec![LitVec::from([!a]), LitVec::from([!b])],
+                Duration::from_secs(1)
+            ),
+            Some(false)
+        );
+        assert!(s.solve(&[]));
+    }
+
+    /// Forgets its model and failed assumptions on `add_clause`, like
+    /// incremental backends do.
+    struct Forgetful(CdclSolver, bool);
+
+    impl Satif for Forgetful {
+        fn new_var(&mut self) -> Var {
+            self.0.new_var()
+        }
+
+        fn num_var(&self) -> usize {
+            self.0.num_var()
+        }
+
+        fn add_clause(&mut self, clause: &[Lit]) {
+            self.1 = false;
+            self.0.add_clause(clause)
+        }
+
+        fn solve(&mut self, assumps: &[Lit]) -> bool {
+            self.1 = true;
+            self.0.solve(assumps)
+        }
+
+        fn sat_value(&self, lit: Lit) -> Option<bool> {
+            assert!(self.1, "model discarded");
+            self.0.sat_value(lit)
+        }
+
+        fn unsat_has(&self, lit: Lit) -> bool {
+            assert!(self.1, "failed assumptions discarded");
+            self.0.unsat_has(lit)
+        }
+    }
+
+    #[test]
+    fn test_constraint_keeps_model() {
+        let mut s = ConstraintSatif::new(Forgetful(CdclSolver::new(), false));
+        let (a, b) = (s.new_var().lit(), s.new_var().lit());
+        s.add_clause(&[a, b]);
+        assert!(s.solve_with_constraint(&[], vec![LitVec::from([!a])]));
+        assert_eq!(s.sat_value(b), Some(true));
+        assert!(!s.solve_with_constraint(&[!b], vec![LitVec::from([!a])]));
+        assert!(s.unsat_has(!b));
+        s.add_clause(&[a, !b]);
+        assert_eq!(s.num_retired(), 2);
+        assert!(s.solve(&[!b]));
+        assert_eq!(s.sat_value(a), Some(true));
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display},
+    ops::{BitAnd, BitOr, BitOrAssign, Not},
+};
+
+/// Set of optional `Satif` capabilities supported by a backend.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SatifFeatures(u32);
+
+impl SatifFeatures {
+    pub const NONE: Self = Self(0);
+    pub const CONSTRAINT: Self = Self(1);
+    pub const LIMIT: Self = Self(1 << 1);
+    pub const UNSAT_CORE: Self = Self(1 << 2);
+    pub const SIMPLIFY: Self = Self(1 << 3);
+    pub const FROZEN: Self = Self(1 << 4);
+    pub const CLAUSES: Self = Self(1 << 5);
+    pub const ALL: Self = Self((1 << 6) - 1);
+
+    const NAMES: [&str; 6] = [
+        "solve_with_constraint",
+        "solve_with_limit",
+        "unsat_has",
+        "simplify",
+        "set_frozen",
+        "clauses",
+    ];
+
+    #[inline]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub fn iter_names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .into_iter()
+            .enumerate()
+            .filter(move |(i, _)| self.0 & (1 << i) != 0)
+            .map(|(_, n)| n)
+    }
+}
+
+impl BitOr for SatifFeatures {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for SatifFeatures {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0
+    }
+}
+
+impl BitAnd for SatifFeatures {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Not for SatifFeatures {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self::Output {
+        Self(!self.0 & Self::ALL.0)
+    }
+}
+
+impl Debug for SatifFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter_names()).finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SatifError {
+    Unsupported(SatifFeatures),
+}
+
+impl Display for SatifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SatifError::Unsupported(feature) => {
+                write!(f, "unsupport ")?;
+                for (i, n) in feature.iter_names().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{n}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for SatifError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Lit, Var,
+        satif::{CdclSolver, Satif},
+    };
+
+    /// Forgets its model on `add_clause`, like incremental backends do.
+    struct Basic(CdclSolver, bool);
+
+    impl Satif for Basic {
+        fn new_var(&mut self) -> Var {
+            self.0.new_var()
+        }
+
+        fn num_var(&self) -> usize {
+            self.0.num_var()
+        }
+
+        fn add_clause(&mut self, clause: &[Lit]) {
+            self.1 = false;
+            self.0.add_clause(clause)
+        }
+
+        fn solve(&mut self, assumps: &[Lit]) -> bool {
+            self.1 = true;
+            self.0.solve(assumps)
+        }
+
+        fn sat_value(&self, lit: Lit) -> Option<bool> {
+            self.0.sat_value(lit).filter(|_| self.1)
+        }
+    }
+
+    #[test]
+    fn test_features() {
+        let mut s = Basic(CdclSolver::new(), false);
+        let (a, b) = (s.new_var().lit(), s.new_var().lit());
+        s.add_clause(&[a, b]);
+        assert_eq!(
+            s.try_solve_with_constraint(&[], vec![[!a].into(), [!b].into()]),
+            Ok(false)
+        );
+        assert_eq!(
+            s.try_solve_with_constraint(&[!a], vec![[!a].into()]),
+            Ok(true)
+        );
+        assert_eq!(s.sat_value(b), Some(true));
+        assert_eq!(s.try_solve_with_constraint(&[!b], vec![]), Ok(true));
+        assert_eq!(
+            s.try_simplify(),
+            Err(SatifError::Unsupported(SatifFeatures::SIMPLIFY))
+        );
+        assert!(s.try_unsat_has(a).is_err());
+        assert!(
+            s.0.check_feature(SatifFeatures::CONSTRAINT | SatifFeatures::LIMIT)
+                .is_ok()
+        );
+        assert_eq!(
+            format!("{:?}", SatifFeatures::LIMIT | SatifFeatures::CLAUSES),
+            "{\"solve_with_limit\", \"clauses\"}"
+        );
+    }
+}
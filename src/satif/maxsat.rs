@@ -0,0 +1,300 @@
+use super::{Satif, SatifFeatures};
+use crate::{DagCnf, Lit, LitVec, Var, VarAssign, dagcnf::Totalizer, wcnf::Wcnf};
+use giputils::hash::GHashMap;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaxSatAlgorithm {
+    /// Core-guided OLL, relaxes cores with totalizers.
+    #[default]
+    Oll,
+    /// Model-improving search bounding the cost with a weighted totalizer.
+    LinearSatUnsat,
+}
+
+#[derive(Clone)]
+pub struct MaxSatResult {
+    pub cost: u64,
+    pub model: VarAssign,
+}
+
+/// Weighted partial MaxSAT over any `Satif`. Soft clauses are relaxed with
+/// fresh literals which are then handled as weighted assumptions.
+pub struct MaxSat<S: Satif> {
+    solver: S,
+    soft: Vec<(LitVec, u64)>,
+    max_var: Var,
+    /// Mirrors the solver's variables, cardinality encodings are built here
+    /// and their clauses loaded into the solver.
+    dc: DagCnf,
+}
+
+impl<S: Satif> MaxSat<S> {
+    #[inline]
+    pub fn new(solver: S) -> Self {
+        Self {
+            solver,
+            soft: Vec::new(),
+            max_var: Var::CONST,
+            dc: DagCnf::new(),
+        }
+    }
+
+    pub fn from_wcnf(solver: S, wcnf: &Wcnf) -> Self {
+        let mut res = Self::new(solver);
+        res.max_var = wcnf.max_var();
+        for cls in wcnf.hard.iter() {
+            res.add_hard(cls);
+        }
+        for (cls, w) in wcnf.soft.iter() {
+            res.add_soft(cls, *w);
+        }
+        res
+    }
+
+    #[inline]
+    fn reserve(&mut self, cls: &[Lit]) {
+        if let Some(m) = cls.iter().map(|l| l.var()).max() {
+            self.max_var = self.max_var.max(m);
+            self.solver.new_var_to(m);
+        }
+    }
+
+    #[inline]
+    pub fn add_hard(&mut self, cls: &[Lit]) {
+        self.reserve(cls);
+        self.solver.add_clause(cls);
+    }
+
+    #[inline]
+    pub fn add_soft(&mut self, cls: &[Lit], weight: u64) {
+        self.reserve(cls);
+        self.soft.push((LitVec::from(cls), weight));
+    }
+
+    /// Returns an optimal model, or `None` if the hard clauses are
+    /// unsatisfiable.
+    pub fn solve(mut self, algorithm: MaxSatAlgorithm) -> Option<MaxSatResult> {
+        self.solver.new_var_to(self.max_var);
+        let viol = self.relax();
+        match algorithm {
+            MaxSatAlgorithm::Oll => self.oll(viol),
+            MaxSatAlgorithm::LinearSatUnsat => self.linear(viol),
+        }
+    }
+
+    /// Adds the relaxed soft clauses and returns the literals that are true
+    /// when a soft clause is violated, with their weights.
+    fn relax(&mut self) -> Vec<(Lit, u64)> {
+        let mut viol: GHashMap<Lit, u64> = GHashMap::new();
+        for (cls, w) in self.soft.clone() {
+            let r = match cls.len() {
+                _ if w == 0 => continue,
+                0 => Lit::constant(true),
+                1 => !cls[0],
+                _ => {
+                    let r = self.solver.new_var().lit();
+                    let mut c = cls.clone();
+                    c.push(r);
+                    self.solver.add_clause(&c);
+                    r
+                }
+            };
+            *viol.entry(r).or_default() += w;
+        }
+        let mut viol: Vec<(Lit, u64)> = viol.into_iter().collect();
+        viol.sort();
+        viol
+    }
+
+    /// Runs `f` on the mirrored `DagCnf` and adds the clauses of the nodes it
+    /// creates to the solver.
+    fn encode<R>(&mut self, f: impl FnOnce(&mut DagCnf) -> R) -> R {
+        let start = self.solver.max_var();
+        self.dc.new_var_to(start);
+        let res = f(&mut self.dc);
+        self.solver.new_var_to(self.dc.max_var());
+        for v in start + 1..=self.dc.max_var() {
+            for cls in self.dc[v].iter() {
+                self.solver.add_clause(cls);
+            }
+        }
+        res
+    }
+
+    fn result(&self) -> MaxSatResult {
+        let vars: Vec<Var> = (1..=*self.max_var).map(Var).collect();
+        let model = self.solver.model(&vars);
+        let cost = self
+            .soft
+            .iter()
+            .filter(|(c, _)| !model.satisfy(c))
+            .map(|(_, w)| w)
+            .sum();
+        MaxSatResult { cost, model }
+    }
+
+    fn linear(&mut self, viol: Vec<(Lit, u64)>) -> Option<MaxSatResult> {
+        if !self.solver.solve(&[]) {
+            return None;
+        }
+        let mut best = self.result();
+        if best.cost == 0 {
+            return Some(best);
+        }
+        let outputs = totalizer(&mut self.solver, &viol, best.cost);
+        loop {
+            let assumps: LitVec = outputs
+                .iter()
+                .filter(|(s, _)| *s >= best.cost)
+                .map(|(_, o)| !*o)
+                .collect();
+            if !self.solver.solve(&assumps) {
+                return Some(best);
+            }
+            best = self.result();
+            if best.cost == 0 {
+                return Some(best);
+            }
+        }
+    }
+
+    fn oll(&mut self, viol: Vec<(Lit, u64)>) -> Option<MaxSatResult> {
+        let mut weight: GHashMap<Lit, u64> = viol.iter().map(|(l, w)| (!*l, *w)).collect();
+        let mut next: GHashMap<Lit, (usize, usize)> = GHashMap::new();
+        let mut totalizers: Vec<Totalizer> = Vec::new();
+        let core_feature = self.solver.features().contains(SatifFeatures::UNSAT_CORE);
+        loop {
+            let mut assumps: Vec<Lit> = weight.keys().copied().collect();
+            assumps.sort();
+            if self.solver.solve(&assumps) {
+                return Some(self.result());
+            }
+            let core: Vec<Lit> = if core_feature {
+                assumps
+                    .into_iter()
+                    .filter(|l| self.solver.unsat_has(*l))
+                    .collect()
+            } else {
+                assumps
+            };
+            if core.is_empty() {
+                return None;
+            }
+            let wmin = core.iter().map(|l| weight[l]).min().unwrap();
+            for a in core.iter() {
+                let w = weight.get_mut(a).unwrap();
+                *w -= wmin;
+                if *w == 0 {
+                    weight.remove(a);
+                }
+                if let Some(&(t, k)) = next.get(a)
+                    && k < totalizers[t].num_input()
+                {
+                    let tot = &mut totalizers[t];
+                    self.encode(|dc| tot.extend_bound(dc, k + 1));
+                    let o = tot.at_least(k + 1);
+                    *weight.entry(!o).or_default() += wmin;
+                    next.insert(!o, (t, k + 1));
+                }
+            }
+            if core.len() > 1 {
+                let inputs: Vec<Lit> = core.iter().map(|l| !*l).collect();
+                let tot = self.encode(|dc| Totalizer::new(dc, &inputs, 2));
+                let o = tot.at_least(2);
+                *weight.entry(!o).or_default() += wmin;
+                next.insert(!o, (totalizers.len(), 2));
+                totalizers.push(tot);
+            }
+        }
+    }
+}
+
+/// Generalized totalizer over weighted inputs for the linear search. Returns
+/// `(s, o)` sorted by `s` where `o` is implied when the weight of the true
+/// inputs reaches `s`, sums of at least `cap` are merged into `cap`.
+fn totalizer<S: Satif + ?Sized>(
+    solver: &mut S,
+    inputs: &[(Lit, u64)],
+    cap: u64,
+) -> Vec<(u64, Lit)> {
+    match inputs {
+        [] => return Vec::new(),
+        [(l, w)] => return vec![((*w).min(cap), *l)],
+        _ => (),
+    }
+    let (l, r) = inputs.split_at(inputs.len() / 2);
+    let left = totalizer(solver, l, cap);
+    let right = totalizer(solver, r, cap);
+    let mut out: BTreeMap<u64, Lit> = BTreeMap::new();
+    let zero = [(0, Lit::constant(true))];
+    for &(a, la) in zero.iter().chain(left.iter()) {
+        for &(b, lb) in zero.iter().chain(right.iter()) {
+            let s = (a + b).min(cap);
+            if s == 0 {
+                continue;
+            }
+            let o = *out.entry(s).or_insert_with(|| solver.new_var().lit());
+            let cls: LitVec = [!la, !lb, o]
+                .into_iter()
+                .filter(|l| *l != Lit::constant(false))
+                .collect();
+            solver.add_clause(&cls);
+        }
+    }
+    out.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::satif::CdclSolver;
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    fn brute_force(num_var: u32, wcnf: &Wcnf) -> Option<u64> {
+        (0..1u32 << num_var)
+            .filter_map(|m| {
+                let mut model = VarAssign::new_with(Var(num_var));
+                for v in 1..=num_var {
+                    model.set(Lit::new(Var(v), (m >> (v - 1)) & 1 == 1));
+                }
+                wcnf.hard
+                    .iter()
+                    .all(|c| model.satisfy(c))
+                    .then(|| wcnf.cost(&model))
+            })
+            .min()
+    }
+
+    #[test]
+    fn test_maxsat() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let num_var = rng.random_range(2..=8);
+            let mut wcnf = Wcnf::new();
+            wcnf.hard.new_var_to(Var(num_var));
+            let rand_cls = |rng: &mut StdRng| -> LitVec {
+                (0..rng.random_range(1..=3))
+                    .map(|_| Lit::new(Var(rng.random_range(1..=num_var)), rng.random()))
+                    .collect()
+            };
+            for _ in 0..rng.random_range(0..=8) {
+                let c = rand_cls(&mut rng);
+                wcnf.add_hard(&c);
+            }
+            for _ in 0..rng.random_range(1..=12) {
+                let c = rand_cls(&mut rng);
+                wcnf.add_soft(&c, rng.random_range(1..=5));
+            }
+            let expect = brute_force(num_var, &wcnf);
+            for algorithm in [MaxSatAlgorithm::Oll, MaxSatAlgorithm::LinearSatUnsat] {
+                let res = MaxSat::from_wcnf(CdclSolver::new(), &wcnf).solve(algorithm);
+                assert_eq!(res.as_ref().map(|r| r.cost), expect);
+                if let Some(res) = res {
+                    assert!(wcnf.hard.check_model(&res.model).is_ok());
+                    assert_eq!(wcnf.cost(&res.model), res.cost);
+                }
+            }
+        }
+    }
+}
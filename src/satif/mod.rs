@@ -0,0 +1,162 @@
+mod allsat;
+mod cdcl;
+mod constraint;
+mod features;
+mod maxsat;
+mod mus;
+mod tracing;
+
+pub use allsat::*;
+pub use cdcl::*;
+pub use constraint::*;
+pub use features::*;
+pub use maxsat::*;
+pub use mus::*;
+pub use tracing::*;
+
+use crate::{Lit, LitVec, Var, VarAssign};
+use std::time::Duration;
+
+pub trait Satif {
+    fn new_var(&mut self) -> Var;
+
+    fn new_var_to(&mut self, var: Var) {
+        while Var::new(self.num_var()) <= var {
+            self.new_var();
+        }
+    }
+
+    fn num_var(&self) -> usize;
+
+    #[inline]
+    fn max_var(&self) -> Var {
+        Var(self.num_var() as u32 - 1)
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]);
+
+    fn solve(&mut self, assumps: &[Lit]) -> bool;
+
+    fn solve_with_constraint(&mut self, _assumps: &[Lit], _constraint: Vec<LitVec>) -> bool {
+        panic!("unsupport solve with constraint");
+    }
+
+    fn solve_with_limit(
+        &mut self,
+        _assumps: &[Lit],
+        _constraint: Vec<LitVec>,
+        _limit: Duration,
+    ) -> Option<bool> {
+        panic!("unsupport solve with limit");
+    }
+
+    fn sat_value(&self, lit: Lit) -> Option<bool>;
+
+    #[inline]
+    fn sat_value_lit(&self, var: Var) -> Option<Lit> {
+        self.sat_value(var.lit()).map(|v| Lit::new(var, v))
+    }
+
+    /// Collects the values of `vars` in the last model. Variables without a
+    /// value are left unassigned.
+    fn model(&self, vars: &[Var]) -> VarAssign {
+        let mut model = VarAssign::new();
+        if let Some(m) = vars.iter().max() {
+            model.reserve(*m);
+        }
+        for &v in vars {
+            if let Some(l) = self.sat_value_lit(v) {
+                model.set(l);
+            }
+        }
+        model
+    }
+
+    fn unsat_has(&self, _lit: Lit) -> bool {
+        panic!("unsupport assumption");
+    }
+
+    fn simplify(&mut self) -> Option<bool> {
+        panic!("unsupport simplify");
+    }
+
+    fn set_frozen(&mut self, _var: Var, _frozen: bool) {
+        panic!("unsupport set frozen");
+    }
+
+    fn clauses(&self) -> Vec<LitVec> {
+        panic!("unsupport get clauses");
+    }
+
+    /// The optional methods that are implemented. Calling an unsupported one
+    /// panics, the `try_` variants report `SatifError::Unsupported` instead.
+    #[inline]
+    fn features(&self) -> SatifFeatures {
+        SatifFeatures::NONE
+    }
+
+    #[inline]
+    fn check_feature(&self, feature: SatifFeatures) -> Result<(), SatifError> {
+        let missing = feature & !self.features();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(SatifError::Unsupported(missing))
+        }
+    }
+
+    /// Solves under temporary constraints, emulating them with a fresh
+    /// activation literal when the backend lacks native support. The
+    /// emulated constraint is disabled by never assuming its literal again,
+    /// no clause is added after the solve so the model and the failed
+    /// assumptions stay readable. Wrap the solver in [`ConstraintSatif`] to
+    /// also retire the dead clauses.
+    fn try_solve_with_constraint(
+        &mut self,
+        assumps: &[Lit],
+        constraint: Vec<LitVec>,
+    ) -> Result<bool, SatifError> {
+        if self.features().contains(SatifFeatures::CONSTRAINT) {
+            return Ok(self.solve_with_constraint(assumps, constraint));
+        }
+        let (assumps, act) = constraint::activate(self, assumps, constraint);
+        let res = self.solve(&assumps);
+        if let Some(act) = act
+            && self.features().contains(SatifFeatures::FROZEN)
+        {
+            self.set_frozen(act, false);
+        }
+        Ok(res)
+    }
+
+    fn try_solve_with_limit(
+        &mut self,
+        assumps: &[Lit],
+        constraint: Vec<LitVec>,
+        limit: Duration,
+    ) -> Result<Option<bool>, SatifError> {
+        self.check_feature(SatifFeatures::LIMIT)?;
+        Ok(self.solve_with_limit(assumps, constraint, limit))
+    }
+
+    fn try_unsat_has(&self, lit: Lit) -> Result<bool, SatifError> {
+        self.check_feature(SatifFeatures::UNSAT_CORE)?;
+        Ok(self.unsat_has(lit))
+    }
+
+    fn try_simplify(&mut self) -> Result<Option<bool>, SatifError> {
+        self.check_feature(SatifFeatures::SIMPLIFY)?;
+        Ok(self.simplify())
+    }
+
+    fn try_set_frozen(&mut self, var: Var, frozen: bool) -> Result<(), SatifError> {
+        self.check_feature(SatifFeatures::FROZEN)?;
+        self.set_frozen(var, frozen);
+        Ok(())
+    }
+
+    fn try_clauses(&self) -> Result<Vec<LitVec>, SatifError> {
+        self.check_feature(SatifFeatures::CLAUSES)?;
+        Ok(self.clauses())
+    }
+}
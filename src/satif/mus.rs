@@ -0,0 +1,196 @@
+use super::{Satif, SatifFeatures};
+use crate::{Lit, LitVec};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MusAlgorithm {
+    /// Tries to drop each assumption in turn, one solve per assumption.
+    #[default]
+    Deletion,
+    /// Divide and conquer, fewer solves when the core is small.
+    QuickXplain,
+}
+
+/// Minimal unsatisfiable subset extraction over assumptions. The solver must
+/// be unsatisfiable under the given assumptions. `unsat_has` is used to shrink
+/// intermediate cores when the backend supports it.
+#[derive(Debug, Clone, Default)]
+pub struct MusExtractor {
+    algorithm: MusAlgorithm,
+    limit: Option<Duration>,
+    deadline: Option<Instant>,
+    timeout: bool,
+    num_solve: usize,
+}
+
+impl MusExtractor {
+    #[inline]
+    pub fn new(algorithm: MusAlgorithm) -> Self {
+        Self {
+            algorithm,
+            ..Default::default()
+        }
+    }
+
+    /// Limits the total time spent in `extract`. When it runs out the current
+    /// core is returned, which is unsatisfiable but possibly not minimal.
+    #[inline]
+    pub fn with_limit(mut self, limit: Duration) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether the last `extract` ran out of time.
+    #[inline]
+    pub fn timeout(&self) -> bool {
+        self.timeout
+    }
+
+    #[inline]
+    pub fn num_solve(&self) -> usize {
+        self.num_solve
+    }
+
+    fn solve<S: Satif + ?Sized>(&mut self, solver: &mut S, assumps: &[Lit]) -> Option<bool> {
+        if self.timeout {
+            return None;
+        }
+        self.num_solve += 1;
+        let res = match self.deadline {
+            Some(deadline) => {
+                let remain = deadline.saturating_duration_since(Instant::now());
+                if remain.is_zero() {
+                    None
+                } else {
+                    solver
+                        .try_solve_with_limit(assumps, vec![], remain)
+                        .unwrap_or_else(|_| Some(solver.solve(assumps)))
+                }
+            }
+            None => Some(solver.solve(assumps)),
+        };
+        self.timeout = res.is_none();
+        res
+    }
+
+    fn refine<S: Satif + ?Sized>(solver: &S, assumps: &[Lit]) -> LitVec {
+        if solver.features().contains(SatifFeatures::UNSAT_CORE) {
+            assumps
+                .iter()
+                .filter(|l| solver.unsat_has(**l))
+                .copied()
+                .collect()
+        } else {
+            LitVec::from(assumps)
+        }
+    }
+
+    fn deletion<S: Satif + ?Sized>(&mut self, solver: &mut S, mut core: LitVec) -> LitVec {
+        let mut i = 0;
+        while i < core.len() {
+            let mut cand = core.clone();
+            cand.remove(i);
+            match self.solve(solver, &cand) {
+                Some(false) => core = Self::refine(solver, &cand),
+                Some(true) => i += 1,
+                None => break,
+            }
+        }
+        core
+    }
+
+    fn quick_xplain<S: Satif + ?Sized>(
+        &mut self,
+        solver: &mut S,
+        background: &mut LitVec,
+        delta: bool,
+        cand: &[Lit],
+    ) -> LitVec {
+        if delta && self.solve(solver, background) == Some(false) {
+            return LitVec::new();
+        }
+        if cand.len() == 1 || self.timeout {
+            return LitVec::from(cand);
+        }
+        let (c1, c2) = cand.split_at(cand.len() / 2);
+        let len = background.len();
+        background.extend_from_slice(c1);
+        let x2 = self.quick_xplain(solver, background, true, c2);
+        background.truncate(len);
+        background.extend_from_slice(&x2);
+        let mut x1 = self.quick_xplain(solver, background, !x2.is_empty(), c1);
+        background.truncate(len);
+        x1.extend_from_slice(&x2);
+        x1
+    }
+
+    /// Returns a minimal subset of `assumps` under which `solver` is
+    /// unsatisfiable, or `None` if it is satisfiable under `assumps`.
+    pub fn extract<S: Satif + ?Sized>(
+        &mut self,
+        solver: &mut S,
+        assumps: &[Lit],
+    ) -> Option<LitVec> {
+        self.timeout = false;
+        self.num_solve = 0;
+        self.deadline = self.limit.map(|l| Instant::now() + l);
+        match self.solve(solver, assumps) {
+            Some(false) => (),
+            Some(true) => return None,
+            None => return Some(LitVec::from(assumps)),
+        }
+        let core = Self::refine(solver, assumps);
+        if core.is_empty() {
+            return Some(core);
+        }
+        let res = match self.algorithm {
+            MusAlgorithm::Deletion => self.deletion(solver, core),
+            MusAlgorithm::QuickXplain => {
+                self.quick_xplain(solver, &mut LitVec::new(), false, &core)
+            }
+        };
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        Var,
+        satif::{CdclSolver, Satif},
+    };
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn test_mus() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let mut solver = CdclSolver::new();
+            let num_var = 8;
+            for _ in 0..12 {
+                let c: LitVec = (0..3)
+                    .map(|_| Lit::new(Var(rng.random_range(1..=num_var)), rng.random()))
+                    .collect();
+                solver.add_clause(&c);
+            }
+            let assumps: LitVec = (1..=num_var)
+                .map(|v| Lit::new(Var(v), rng.random()))
+                .collect();
+            if solver.solve(&assumps) {
+                continue;
+            }
+            for algorithm in [MusAlgorithm::Deletion, MusAlgorithm::QuickXplain] {
+                let mut mus = MusExtractor::new(algorithm).with_limit(Duration::from_secs(10));
+                let core = mus.extract(&mut solver, &assumps).unwrap();
+                assert!(!mus.timeout());
+                assert!(!solver.solve(&core));
+                for i in 0..core.len() {
+                    let mut c = core.clone();
+                    c.remove(i);
+                    assert!(solver.solve(&c));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use super::{Satif, SatifFeatures};
+use crate::{Lit, LitVec, Var};
+use log::{Level, log};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default, Clone)]
+pub struct TracingStatistic {
+    pub num_new_var: usize,
+    pub num_add_clause: usize,
+    pub num_solve: usize,
+    pub num_sat: usize,
+    pub num_unsat: usize,
+    pub num_unknown: usize,
+    pub num_set_frozen: usize,
+    pub solve_time: Duration,
+}
+
+/// A `Satif` wrapper that forwards every call to the inner solver and logs it,
+/// together with its result and the time spent, at the configured level.
+pub struct TracingSatif<S: Satif> {
+    solver: S,
+    level: Level,
+    statistic: TracingStatistic,
+}
+
+impl<S: Satif> TracingSatif<S> {
+    #[inline]
+    pub fn new(solver: S) -> Self {
+        Self::new_with_level(solver, Level::Debug)
+    }
+
+    #[inline]
+    pub fn new_with_level(solver: S, level: Level) -> Self {
+        Self {
+            solver,
+            level,
+            statistic: TracingStatistic::default(),
+        }
+    }
+
+    #[inline]
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+    }
+
+    #[inline]
+    pub fn statistic(&self) -> &TracingStatistic {
+        &self.statistic
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.solver
+    }
+
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.solver
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.solver
+    }
+
+    fn record_solve(&mut self, res: Option<bool>, time: Duration) {
+        self.statistic.num_solve += 1;
+        self.statistic.solve_time += time;
+        match res {
+            Some(true) => self.statistic.num_sat += 1,
+            Some(false) => self.statistic.num_unsat += 1,
+            None => self.statistic.num_unknown += 1,
+        }
+    }
+}
+
+impl<S: Satif> Satif for TracingSatif<S> {
+    fn new_var(&mut self) -> Var {
+        let v = self.solver.new_var();
+        self.statistic.num_new_var += 1;
+        log!(self.level, "satif new_var -> {v}");
+        v
+    }
+
+    #[inline]
+    fn num_var(&self) -> usize {
+        self.solver.num_var()
+    }
+
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.statistic.num_add_clause += 1;
+        log!(self.level, "satif add_clause {clause:?}");
+        self.solver.add_clause(clause);
+    }
+
+    fn solve(&mut self, assumps: &[Lit]) -> bool {
+        log!(self.level, "satif solve {assumps:?}");
+        let start = Instant::now();
+        let res = self.solver.solve(assumps);
+        let time = start.elapsed();
+        self.record_solve(Some(res), time);
+        log!(
+            self.level,
+            "satif solve #{} -> {} in {:.6}s",
+            self.statistic.num_solve,
+            if res { "sat" } else { "unsat" },
+            time.as_secs_f64()
+        );
+        res
+    }
+
+    fn solve_with_constraint(&mut self, assumps: &[Lit], constraint: Vec<LitVec>) -> bool {
+        log!(
+            self.level,
+            "satif solve_with_constraint {assumps:?} {constraint:?}"
+        );
+        let start = Instant::now();
+        let res = self.solver.solve_with_constraint(assumps, constraint);
+        let time = start.elapsed();
+        self.record_solve(Some(res), time);
+        log!(
+            self.level,
+            "satif solve #{} -> {} in {:.6}s",
+            self.statistic.num_solve,
+            if res { "sat" } else { "unsat" },
+            time.as_secs_f64()
+        );
+        res
+    }
+
+    fn solve_with_limit(
+        &mut self,
+        assumps: &[Lit],
+        constraint: Vec<LitVec>,
+        limit: Duration,
+    ) -> Option<bool> {
+        log!(
+            self.level,
+            "satif solve_with_limit {assumps:?} {constraint:?} limit {:.6}s",
+            limit.as_secs_f64()
+        );
+        let start = Instant::now();
+        let res = self.solver.solve_with_limit(assumps, constraint, limit);
+        let time = start.elapsed();
+        self.record_solve(res, time);
+        log!(
+            self.level,
+            "satif solve #{} -> {} in {:.6}s",
+            self.statistic.num_solve,
+            match res {
+                Some(true) => "sat",
+                Some(false) => "unsat",
+                None => "unknown",
+            },
+            time.as_secs_f64()
+        );
+        res
+    }
+
+    #[inline]
+    fn features(&self) -> SatifFeatures {
+        self.solver.features()
+    }
+
+    #[inline]
+    fn sat_value(&self, lit: Lit) -> Option<bool> {
+        self.solver.sat_value(lit)
+    }
+
+    fn unsat_has(&self, lit: Lit) -> bool {
+        let res = self.solver.unsat_has(lit);
+        log!(self.level, "satif unsat_has {lit} -> {res}");
+        res
+    }
+
+    fn simplify(&mut self) -> Option<bool> {
+        let start = Instant::now();
+        let res = self.solver.simplify();
+        log!(
+            self.level,
+            "satif simplify -> {res:?} in {:.6}s",
+            start.elapsed().as_secs_f64()
+        );
+        res
+    }
+
+    fn set_frozen(&mut self, var: Var, frozen: bool) {
+        self.statistic.num_set_frozen += 1;
+        log!(self.level, "satif set_frozen {var} {frozen}");
+        self.solver.set_frozen(var, frozen)
+    }
+
+    fn clauses(&self) -> Vec<LitVec> {
+        let res = self.solver.clauses();
+        log!(self.level, "satif clauses -> {} clauses", res.len());
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::satif::CdclSolver;
+
+    #[test]
+    fn test_tracing() {
+        let mut solver = TracingSatif::new(CdclSolver::new());
+        let a = solver.new_var().lit();
+        let b = solver.new_var().lit();
+        assert_eq!(solver.num_var(), solver.inner().num_var());
+        solver.add_clause(&[a, b]);
+        solver.add_clause(&[!a]);
+        solver.set_frozen(a.var(), true);
+        assert!(solver.solve(&[]));
+        assert_eq!(solver.sat_value(b), Some(true));
+        assert!(!solver.solve(&[!b]));
+        assert!(solver.unsat_has(!b));
+        assert!(!solver.solve_with_constraint(&[], vec![LitVec::from([!b])]));
+        let limit = Duration::from_secs(10);
+        assert_eq!(solver.solve_with_limit(&[b], vec![], limit), Some(true));
+        assert_eq!(solver.clauses(), solver.inner().clauses());
+        let st = solver.statistic();
+        assert_eq!(
+            (st.num_new_var, st.num_add_clause, st.num_set_frozen),
+            (2, 2, 1)
+        );
+        assert_eq!((st.num_solve, st.num_sat, st.num_unsat), (4, 2, 2));
+        assert_eq!(st.num_unknown, 0);
+        assert_eq!(solver.into_inner().sat_value(b), Some(true));
+    }
+}
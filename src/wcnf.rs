@@ -0,0 +1,261 @@
+use crate::{
+    Cnf, Lit, LitVec, Var, VarAssign,
+    dimacs::{DimacsError, DimacsErrorKind, create_compressed, open_compressed},
+};
+use std::{
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+/// A weighted partial MaxSAT instance. Variables follow the `Cnf` numbering,
+/// `Var(k)` is DIMACS variable `k`.
+#[derive(Debug, Clone, Default)]
+pub struct Wcnf {
+    pub hard: Cnf,
+    pub soft: Vec<(LitVec, u64)>,
+}
+
+impl Wcnf {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn max_var(&self) -> Var {
+        self.hard.max_var()
+    }
+
+    #[inline]
+    pub fn add_hard(&mut self, cls: &[Lit]) {
+        self.hard.add_clause(cls);
+    }
+
+    #[inline]
+    pub fn add_soft(&mut self, cls: &[Lit], weight: u64) {
+        if let Some(m) = cls.iter().map(|l| l.var()).max() {
+            self.hard.new_var_to(m);
+        }
+        self.soft.push((LitVec::from(cls), weight));
+    }
+
+    /// Sum of the weights of the soft clauses falsified by `model`.
+    pub fn cost(&self, model: &VarAssign) -> u64 {
+        self.soft
+            .iter()
+            .filter(|(c, _)| !model.satisfy(c))
+            .map(|(_, w)| w)
+            .sum()
+    }
+}
+
+struct WcnfParser {
+    line: usize,
+    num_var: u32,
+    top: Option<u64>,
+    header: bool,
+    has_clause: bool,
+    weight: Option<Option<u64>>,
+    clause: LitVec,
+    clause_pos: (usize, usize),
+}
+
+impl WcnfParser {
+    fn error(&self, col: usize, kind: DimacsErrorKind) -> DimacsError {
+        DimacsError::new(self.line, col, kind)
+    }
+
+    fn parse_header(&mut self, tokens: &[(usize, &str)]) -> Result<(), DimacsError> {
+        let col = tokens[0].0;
+        if self.header {
+            return Err(self.error(col, DimacsErrorKind::DuplicateHeader));
+        }
+        if self.has_clause {
+            return Err(self.error(col, DimacsErrorKind::HeaderAfterClause));
+        }
+        if tokens.len() < 4 || tokens.len() > 5 || tokens[0].1 != "p" || tokens[1].1 != "wcnf" {
+            return Err(self.error(col, DimacsErrorKind::InvalidHeader));
+        }
+        for &(c, t) in tokens[2..].iter() {
+            t.parse::<u64>()
+                .map_err(|_| self.error(c, DimacsErrorKind::InvalidHeader))?;
+        }
+        self.header = true;
+        self.num_var = tokens[2].1.parse().unwrap();
+        self.top = tokens.get(4).map(|(_, t)| t.parse().unwrap());
+        Ok(())
+    }
+
+    fn parse_line(&mut self, line: &str, wcnf: &mut Wcnf) -> Result<(), DimacsError> {
+        let tokens: Vec<(usize, &str)> = line
+            .split_ascii_whitespace()
+            .map(|t| (t.as_ptr() as usize - line.as_ptr() as usize + 1, t))
+            .collect();
+        let Some(&(_, first)) = tokens.first() else {
+            return Ok(());
+        };
+        if first.starts_with('c') {
+            return Ok(());
+        }
+        if first.starts_with('p') {
+            self.parse_header(&tokens)?;
+            wcnf.hard.new_var_to(Var(self.num_var));
+            return Ok(());
+        }
+        for &(col, token) in tokens.iter() {
+            let Some(weight) = self.weight else {
+                self.clause_pos = (self.line, col);
+                self.has_clause = true;
+                let weight = if token == "h" {
+                    None
+                } else {
+                    let w: u64 = token.parse().map_err(|_| {
+                        self.error(col, DimacsErrorKind::InvalidWeight(token.to_string()))
+                    })?;
+                    if self.top.is_some_and(|t| w >= t) {
+                        None
+                    } else {
+                        Some(w)
+                    }
+                };
+                self.weight = Some(weight);
+                continue;
+            };
+            let invalid = || self.error(col, DimacsErrorKind::InvalidLiteral(token.to_string()));
+            let lit: i64 = token.parse().map_err(|_| invalid())?;
+            if lit == 0 {
+                let cls = std::mem::take(&mut self.clause);
+                match weight {
+                    Some(w) => wcnf.add_soft(&cls, w),
+                    None => wcnf.add_hard(&cls),
+                }
+                self.weight = None;
+                continue;
+            }
+            let var = u32::try_from(lit.unsigned_abs()).map_err(|_| invalid())?;
+            if var >= u32::MAX >> 1 {
+                return Err(invalid());
+            }
+            if self.header && var > self.num_var {
+                let num_var = self.num_var;
+                return Err(self.error(col, DimacsErrorKind::VarOutOfRange { var, num_var }));
+            }
+            self.clause.push(Lit::new(Var(var), lit > 0));
+        }
+        Ok(())
+    }
+}
+
+/// Reads a WCNF instance, both the `p wcnf` format with a top weight and the
+/// header-less format with `h` marking hard clauses are accepted.
+pub fn read_wcnf<R: BufRead>(mut reader: R) -> Result<Wcnf, DimacsError> {
+    let mut wcnf = Wcnf::new();
+    let mut parser = WcnfParser {
+        line: 0,
+        num_var: 0,
+        top: None,
+        header: false,
+        has_clause: false,
+        weight: None,
+        clause: LitVec::new(),
+        clause_pos: (0, 0),
+    };
+    let mut buf = String::new();
+    loop {
+        buf.clear();
+        let n = reader
+            .read_line(&mut buf)
+            .map_err(|e| DimacsError::new(parser.line + 1, 0, DimacsErrorKind::Io(e)))?;
+        if n == 0 {
+            break;
+        }
+        parser.line += 1;
+        parser.parse_line(&buf, &mut wcnf)?;
+    }
+    if parser.weight.is_some() {
+        let (line, col) = parser.clause_pos;
+        return Err(DimacsError::new(
+            line,
+            col,
+            DimacsErrorKind::UnterminatedClause,
+        ));
+    }
+    Ok(wcnf)
+}
+
+pub fn read_wcnf_str(str: &str) -> Result<Wcnf, DimacsError> {
+    read_wcnf(str.as_bytes())
+}
+
+pub fn read_wcnf_file<P: AsRef<Path>>(file: P) -> Result<Wcnf, DimacsError> {
+    let file = open_compressed(file).map_err(|e| DimacsError::new(0, 0, DimacsErrorKind::Io(e)))?;
+    read_wcnf(file)
+}
+
+/// Writes `wcnf` in the header-less format. The constant clause of the hard
+/// `Cnf` is skipped.
+pub fn write_wcnf<W: Write>(wcnf: &Wcnf, mut out: W) -> io::Result<()> {
+    let constant = LitVec::from(Lit::constant(true));
+    for cls in wcnf.hard.iter().filter(|c| **c != constant) {
+        write!(out, "h ")?;
+        for l in cls.iter() {
+            write!(out, "{l} ")?;
+        }
+        writeln!(out, "0")?;
+    }
+    for (cls, w) in wcnf.soft.iter() {
+        write!(out, "{w} ")?;
+        for l in cls.iter() {
+            write!(out, "{l} ")?;
+        }
+        writeln!(out, "0")?;
+    }
+    Ok(())
+}
+
+pub fn write_wcnf_file<P: AsRef<Path>>(wcnf: &Wcnf, file: P) -> io::Result<()> {
+    let mut file = create_compressed(file)?;
+    write_wcnf(wcnf, &mut file)?;
+    file.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wcnf() {
+        let old =
+            read_wcnf_str("c old\np wcnf 3 4 10\n10 1 -2 0\n3 2\n 0\n5 -3 0\n10 3 -1 0\n").unwrap();
+        let new = read_wcnf_str("c new\nh 1 -2 0\n3 2 0\n5 -3 0\nh 3 -1 0\n").unwrap();
+        for wcnf in [&old, &new] {
+            assert_eq!(wcnf.max_var(), Var(3));
+            assert_eq!(wcnf.hard.len(), 3);
+            assert_eq!(
+                wcnf.soft,
+                vec![
+                    (LitVec::from([Lit::from(2)]), 3),
+                    (LitVec::from([Lit::from(-3)]), 5)
+                ]
+            );
+        }
+        let mut out = Vec::new();
+        write_wcnf(&new, &mut out).unwrap();
+        let back = read_wcnf(out.as_slice()).unwrap();
+        assert_eq!(back.hard.clauses(), new.hard.clauses());
+        assert_eq!(back.soft, new.soft);
+        assert!(read_wcnf_str("x 1 0\n").is_err());
+        assert!(read_wcnf_str("h 1 2\n").is_err());
+        let err = read_wcnf_str("p wcnf 2 1 10\n10 1 -3 0\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 6));
+        assert!(matches!(
+            err.kind,
+            DimacsErrorKind::VarOutOfRange { var: 3, num_var: 2 }
+        ));
+        let err = read_wcnf_str("h 1 0\np wcnf 1 1 10\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(matches!(err.kind, DimacsErrorKind::HeaderAfterClause));
+        let err = read_wcnf_str("p wcnf 1 1 10\np wcnf 1 1 10\n").unwrap_err();
+        assert!(matches!(err.kind, DimacsErrorKind::DuplicateHeader));
+    }
+}